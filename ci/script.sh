@@ -6,5 +6,9 @@ pushd src/input
 cargo test -v
 popd
 
+pushd src/event_loop
+cargo test -v --features record
+popd
+
 cargo build -v
 cargo doc -v
@@ -0,0 +1,231 @@
+//! Merging of high-frequency motion events in the event loop.
+
+use std::time::Duration;
+
+use input::{
+    coalesce::{can_coalesce, Coalescer},
+    Event,
+};
+use window::{Size, Window};
+
+use crate::{EventLoop, EventSettings, Events};
+
+/// An event loop iterator merging high-frequency motion events.
+///
+/// Mice and tablets with a high polling rate might deliver hundreds of motion events per frame.
+/// This wraps [`Events`] and merges input events with an [`input::coalesce::Coalescer`]:
+/// cursor moves keep the latest position, relative motion and scroll deltas are summed,
+/// and touch moves are merged per finger.
+///
+/// ```ignore
+/// let mut events = CoalescedEvents::new(Events::new(EventSettings::new())).motion_history(true);
+/// while let Some(e) = events.next(&mut window) {
+///     if e.mouse_cursor_args().is_some() {
+///         for original in events.merged_events() {
+///             canvas.draw(original);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CoalescedEvents {
+    events: Events,
+    coalescer: Coalescer,
+}
+
+impl CoalescedEvents {
+    /// Creates a new event iterator merging the input events of an event loop.
+    ///
+    /// The original motion events are not kept.
+    pub fn new(events: Events) -> CoalescedEvents {
+        CoalescedEvents {
+            events,
+            coalescer: Coalescer::new(false),
+        }
+    }
+
+    /// Enable or disable keeping the original motion events that are merged.
+    /// Used by applications that need every position, e.g. drawing applications.
+    pub fn set_motion_history(&mut self, enable: bool) {
+        self.coalescer.set_keep_history(enable);
+    }
+
+    /// Enable or disable keeping the original motion events that are merged.
+    /// Used by applications that need every position, e.g. drawing applications.
+    pub fn motion_history(mut self, enable: bool) -> Self {
+        self.set_motion_history(enable);
+        self
+    }
+
+    /// Returns the original motion events merged into the last input event, in order.
+    ///
+    /// This is empty when motion history is disabled,
+    /// or when the last input event was not motion.
+    pub fn merged_events(&self) -> &[Event] {
+        self.coalescer.history()
+    }
+
+    /// Returns the wrapped event loop iterator.
+    pub fn events(&self) -> &Events {
+        &self.events
+    }
+
+    /// Returns the next event.
+    pub fn next<W>(&mut self, window: &mut W) -> Option<Event>
+    where
+        W: Window,
+    {
+        self.events.next(&mut CoalescingWindow {
+            window,
+            coalescer: &mut self.coalescer,
+        })
+    }
+
+    /// Returns the next event.
+    #[cfg(feature = "async")]
+    pub async fn async_next<W>(&mut self, window: &mut W) -> Option<Event>
+    where
+        W: Window,
+    {
+        self.events
+            .async_next(&mut CoalescingWindow {
+                window,
+                coalescer: &mut self.coalescer,
+            })
+            .await
+    }
+}
+
+impl EventLoop for CoalescedEvents {
+    fn get_event_settings(&self) -> EventSettings {
+        self.events.get_event_settings()
+    }
+    fn set_event_settings(&mut self, settings: EventSettings) {
+        // Events waiting in the coalescer are kept.
+        self.events.set_event_settings(settings);
+    }
+}
+
+/// Merges the input events of a window, while polled by the event loop.
+struct CoalescingWindow<'a, W> {
+    window: &'a mut W,
+    coalescer: &'a mut Coalescer,
+}
+
+impl<W: Window> CoalescingWindow<'_, W> {
+    /// Adds an input event and the following motion events of the window.
+    fn gather(&mut self, e: Event) {
+        let mut motion = can_coalesce(&e);
+        self.coalescer.push(e);
+        // Stop at the first event that is not motion, to not delay it.
+        while motion {
+            match self.window.poll_event() {
+                Some(e) => {
+                    motion = can_coalesce(&e);
+                    self.coalescer.push(e);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+impl<W: Window> Window for CoalescingWindow<'_, W> {
+    fn set_should_close(&mut self, value: bool) {
+        self.window.set_should_close(value);
+    }
+
+    fn should_close(&self) -> bool {
+        self.window.should_close()
+    }
+
+    fn size(&self) -> Size {
+        self.window.size()
+    }
+
+    fn swap_buffers(&mut self) {
+        self.window.swap_buffers();
+    }
+
+    fn wait_event(&mut self) -> Event {
+        if self.coalescer.is_empty() {
+            let e = self.window.wait_event();
+            self.gather(e);
+        }
+        match self.coalescer.pop() {
+            Some(e) => e,
+            None => self.window.wait_event(),
+        }
+    }
+
+    fn wait_event_timeout(&mut self, timeout: Duration) -> Option<Event> {
+        if self.coalescer.is_empty() {
+            let e = self.window.wait_event_timeout(timeout)?;
+            self.gather(e);
+        }
+        self.coalescer.pop()
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        if self.coalescer.is_empty() {
+            let e = self.window.poll_event()?;
+            self.gather(e);
+        }
+        self.coalescer.pop()
+    }
+
+    fn draw_size(&self) -> Size {
+        self.window.draw_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input::{Button, ButtonArgs, ButtonState, Input, Motion, MouseButton};
+    use window::{NoWindow, WindowSettings};
+
+    fn push_input(window: &mut NoWindow) {
+        for i in 0..4 {
+            window.push_event((Input::Move(Motion::MouseCursor([i as f64, 0.0])), Some(i)));
+        }
+        let press = ButtonArgs::new(ButtonState::Press, Button::Mouse(MouseButton::Left), None);
+        window.push_event((Input::Button(press), Some(4)));
+        window.push_event((Input::Move(Motion::MouseCursor([5.0, 0.0])), Some(5)));
+    }
+
+    fn input_events<F>(next: F) -> Vec<Event>
+    where
+        F: FnMut() -> Option<Event>,
+    {
+        std::iter::from_fn(next)
+            .take_while(|e| matches!(e, Event::Input(..)))
+            .collect()
+    }
+
+    #[test]
+    fn test_coalesced_events() {
+        let mut window = NoWindow::new(&WindowSettings::new("test", [100, 100]));
+        let settings = EventSettings::new().lazy(true);
+
+        push_input(&mut window);
+        let mut events = Events::new(settings);
+        assert_eq!(input_events(|| events.next(&mut window)).len(), 6);
+
+        push_input(&mut window);
+        let mut events = CoalescedEvents::new(Events::new(settings)).motion_history(true);
+        let e = events.next(&mut window).unwrap();
+        assert_eq!(
+            e,
+            (Input::Move(Motion::MouseCursor([3.0, 0.0])), Some(3)).into()
+        );
+        assert_eq!(events.merged_events().len(), 4);
+        assert_eq!(events.merged_events()[3], e);
+        let rest = input_events(|| events.next(&mut window));
+        assert_eq!(rest.len(), 2);
+        assert_eq!(
+            rest[1],
+            (Input::Move(Motion::MouseCursor([5.0, 0.0])), Some(5)).into()
+        );
+    }
+}
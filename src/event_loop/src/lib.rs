@@ -13,6 +13,10 @@
     missing_debug_implementations
 )]
 
+#[cfg(feature = "record")]
+#[macro_use]
+extern crate serde_derive;
+
 use std::{
     cmp,
     time::{Duration, Instant},
@@ -21,6 +25,12 @@ use std::{
 use input::{AfterRenderArgs, Event, IdleArgs, RenderArgs, UpdateArgs};
 use window::Window;
 
+pub use coalesce::CoalescedEvents;
+
+mod coalesce;
+#[cfg(feature = "record")]
+pub mod record;
+
 /// Tells whether last emitted event was idle or not.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum Idle {
@@ -367,11 +377,7 @@ impl Events {
             state: State::Render,
             last_update: start,
             last_frame: start,
-            dt_update_in_ns: if settings.ups == 0 {
-                0
-            } else {
-                BILLION / settings.ups
-            },
+            dt_update_in_ns: BILLION.checked_div(settings.ups).unwrap_or(0),
             dt_frame_in_ns: BILLION / settings.max_fps,
             dt: if settings.ups == 0 {
                 0.0
@@ -399,7 +405,7 @@ impl Events {
         W: Window,
     {
         let sleep = tokio::time::sleep;
-        next_with_sleep!(self, window, sleep .await)
+        next_with_sleep!(self, window, sleep.await)
     }
 }
 
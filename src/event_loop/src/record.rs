@@ -0,0 +1,423 @@
+//! Recording and replay of events.
+//!
+//! A recording is a text file starting with a header line,
+//! followed by one JSON record per line.
+//! Each record stores an event together with the time in seconds
+//! since the recording started.
+//! Custom events are recorded with the functions of a [`Registry`],
+//! and recording or reading an unregistered custom event returns an error.
+//!
+//! This module requires the `record` feature.
+//!
+//! ```ignore
+//! let mut recorder = Recorder::create("session.events")?;
+//! while let Some(e) = events.next(&mut window) {
+//!     recorder.record(&e, &registry)?;
+//!     ...
+//! }
+//! ```
+//!
+//! To replay, use a [`ReplayWindow`] with an event loop that does not emit update events,
+//! since the recorded update events are replayed:
+//!
+//! ```ignore
+//! let mut window = ReplayWindow::open("session.events", ReplaySpeed::Fast, &registry)?;
+//! let mut events = Events::new(EventSettings::new().ups(0));
+//! while let Some(e) = events.next(&mut window) {
+//!     ...
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use input::{
+    custom::{RegisteredEvent, Registry},
+    CloseArgs, Event, Input, Loop,
+};
+use serde::de::DeserializeSeed;
+use window::{Size, Window};
+
+/// The format name stored in the header of recordings.
+pub const RECORDING_FORMAT: &str = "piston-events";
+/// The version of the recording format written by [`Recorder`].
+pub const RECORDING_VERSION: u32 = 1;
+
+/// The first line of a recording.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct Header {
+    format: String,
+    version: u32,
+}
+
+/// Stores an event with the time it was recorded.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Record {
+    /// The time in seconds since the recording started.
+    pub time: f64,
+    /// The recorded event.
+    pub event: Event,
+}
+
+/// Serializes a record, using a registry for custom events.
+#[derive(Serialize)]
+struct RecordRef<'a> {
+    time: f64,
+    event: RegisteredEvent<'a>,
+}
+
+/// A record with the event not yet deserialized,
+/// since custom events require a registry.
+#[derive(Deserialize)]
+struct RawRecord {
+    time: f64,
+    event: serde_json::Value,
+}
+
+fn invalid_data<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Reads records from a recording, using a registry for custom events.
+///
+/// Returns an error if the header is missing, the version is not supported
+/// or a custom event is not registered.
+pub fn read_records<R: BufRead>(reader: R, registry: &Registry) -> io::Result<Vec<Record>> {
+    let mut lines = reader.lines();
+    let header: Header = match lines.next() {
+        Some(line) => serde_json::from_str(&line?).map_err(invalid_data)?,
+        None => return Err(invalid_data("missing recording header")),
+    };
+    if header.format != RECORDING_FORMAT {
+        return Err(invalid_data(format!(
+            "unknown recording format `{}`",
+            header.format
+        )));
+    }
+    if header.version != RECORDING_VERSION {
+        return Err(invalid_data(format!(
+            "unsupported recording version {}, expected {}",
+            header.version, RECORDING_VERSION
+        )));
+    }
+    let mut records = vec![];
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: RawRecord = serde_json::from_str(&line).map_err(invalid_data)?;
+        records.push(Record {
+            time: record.time,
+            event: registry
+                .seed()
+                .deserialize(record.event)
+                .map_err(invalid_data)?,
+        });
+    }
+    Ok(records)
+}
+
+/// Writes events to a recording.
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates a recording file.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Recorder::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a new recorder, writing the header.
+    ///
+    /// Time of records is measured from when the recorder is created.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let header = Header {
+            format: RECORDING_FORMAT.into(),
+            version: RECORDING_VERSION,
+        };
+        serde_json::to_writer(&mut writer, &header)?;
+        writer.write_all(b"\n")?;
+        Ok(Recorder {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// Records an event, using a registry for custom events.
+    ///
+    /// Returns an error if a custom event is not registered.
+    /// Nothing is written in that case.
+    pub fn record(&mut self, e: &Event, registry: &Registry) -> io::Result<()> {
+        let record = RecordRef {
+            time: self.start.elapsed().as_secs_f64(),
+            event: registry.event(e),
+        };
+        let mut line = serde_json::to_vec(&record).map_err(invalid_data)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)
+    }
+
+    /// Flushes the recording.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// How fast to replay a recording.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    /// Replay events at the time they were recorded.
+    RealTime,
+    /// Replay events as fast as possible.
+    Fast,
+}
+
+/// A window that replays recorded events.
+///
+/// Input, custom and update events are replayed.
+/// Render, after render and idle events are skipped,
+/// since the event loop emits these.
+///
+/// The window closes when all events are replayed.
+#[derive(Debug)]
+pub struct ReplayWindow {
+    records: VecDeque<Record>,
+    speed: ReplaySpeed,
+    start: Option<Instant>,
+    size: Size,
+    draw_size: Size,
+    should_close: bool,
+}
+
+impl ReplayWindow {
+    /// Creates a new replay window from records.
+    ///
+    /// The window size is taken from the first render or resize event.
+    pub fn new(records: Vec<Record>, speed: ReplaySpeed) -> ReplayWindow {
+        let (size, draw_size) = records
+            .iter()
+            .find_map(|record| match record.event {
+                Event::Loop(Loop::Render(args)) => {
+                    Some((args.window_size.into(), args.draw_size.into()))
+                }
+                Event::Input(Input::Resize(args), _) => {
+                    Some((args.window_size.into(), args.draw_size.into()))
+                }
+                _ => None,
+            })
+            .unwrap_or(([0.0; 2].into(), [0.0; 2].into()));
+        let records = records
+            .into_iter()
+            .filter(|record| match record.event {
+                Event::Input(..) | Event::Custom(..) => true,
+                Event::Loop(Loop::Update(_)) => true,
+                Event::Loop(_) => false,
+            })
+            .collect();
+        ReplayWindow {
+            records,
+            speed,
+            start: None,
+            size,
+            draw_size,
+            should_close: false,
+        }
+    }
+
+    /// Opens a recording file, using a registry for custom events.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        speed: ReplaySpeed,
+        registry: &Registry,
+    ) -> io::Result<ReplayWindow> {
+        let records = read_records(BufReader::new(File::open(path)?), registry)?;
+        Ok(ReplayWindow::new(records, speed))
+    }
+
+    /// Returns the number of events left to replay.
+    pub fn remaining(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns the time until the next event is due, or `None` if there are no more events.
+    fn time_to_next(&mut self) -> Option<Duration> {
+        let time = self.records.front()?.time;
+        match self.speed {
+            ReplaySpeed::Fast => Some(Duration::from_secs(0)),
+            ReplaySpeed::RealTime => {
+                let start = *self.start.get_or_insert_with(Instant::now);
+                let elapsed = start.elapsed().as_secs_f64();
+                Some(Duration::from_secs_f64((time - elapsed).max(0.0)))
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<Event> {
+        let record = self.records.pop_front()?;
+        if let Event::Input(Input::Resize(args), _) = record.event {
+            self.size = args.window_size.into();
+            self.draw_size = args.draw_size.into();
+        }
+        Some(record.event)
+    }
+}
+
+impl Window for ReplayWindow {
+    fn set_should_close(&mut self, value: bool) {
+        self.should_close = value;
+    }
+
+    fn should_close(&self) -> bool {
+        self.should_close || self.records.is_empty()
+    }
+
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn swap_buffers(&mut self) {}
+
+    fn wait_event(&mut self) -> Event {
+        match self.time_to_next() {
+            Some(dt) => {
+                thread::sleep(dt);
+                self.pop().unwrap()
+            }
+            None => {
+                self.should_close = true;
+                Event::Input(Input::Close(CloseArgs), None)
+            }
+        }
+    }
+
+    fn wait_event_timeout(&mut self, timeout: Duration) -> Option<Event> {
+        let dt = self.time_to_next()?;
+        if dt > timeout {
+            thread::sleep(timeout);
+            None
+        } else {
+            thread::sleep(dt);
+            self.pop()
+        }
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        if self.time_to_next()? == Duration::from_secs(0) {
+            self.pop()
+        } else {
+            None
+        }
+    }
+
+    fn draw_size(&self) -> Size {
+        self.draw_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input::{
+        event_id::EventId, Button, ButtonArgs, ButtonState, GenericEvent, Key, RenderArgs,
+        UpdateArgs,
+    };
+    use std::sync::Arc;
+
+    const SCORE: EventId = EventId("test/record_score");
+
+    #[test]
+    fn test_record_replay() {
+        let press: Event = (
+            Input::Button(ButtonArgs::new(
+                ButtonState::Press,
+                Button::Keyboard(Key::A),
+                None,
+            )),
+            Some(10),
+        )
+            .into();
+        let update: Event = UpdateArgs { dt: 0.5 }.into();
+        let render: Event = RenderArgs {
+            ext_dt: 0.0,
+            window_size: [100.0, 50.0],
+            draw_size: [200, 100],
+        }
+        .into();
+
+        let score = Event::Custom(SCORE, Arc::new(7_u32), Some(20));
+        let mut registry = Registry::new();
+        registry.register::<u32>(SCORE);
+
+        let mut recorder = Recorder::new(vec![]).unwrap();
+        for e in &[render, press.clone(), score.clone(), update.clone()] {
+            recorder.record(e, &registry).unwrap();
+        }
+        let data = recorder.into_inner();
+        let records = read_records(&data[..], &registry).unwrap();
+        assert_eq!(records.len(), 4);
+
+        let mut window = ReplayWindow::new(records, ReplaySpeed::Fast);
+        assert_eq!(window.draw_size(), [200, 100].into());
+        assert_eq!(window.poll_event(), Some(press));
+        let e = window.poll_event().unwrap();
+        assert_eq!(e.time_stamp(), Some(20));
+        match e {
+            Event::Custom(id, args, _) => {
+                assert_eq!(id, SCORE);
+                assert_eq!(args.downcast_ref::<u32>(), Some(&7));
+            }
+            _ => panic!("Expected custom event"),
+        }
+        assert_eq!(window.poll_event(), Some(update));
+        assert_eq!(window.poll_event(), None);
+        assert!(window.should_close());
+    }
+
+    #[test]
+    fn test_read_records_version() {
+        let data = b"{\"format\":\"piston-events\",\"version\":999}\n";
+        let err = read_records(&data[..], &Registry::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_record_unregistered() {
+        let score = Event::Custom(SCORE, Arc::new(7_u32), None);
+        let mut registry = Registry::new();
+        registry.register::<u32>(SCORE);
+        let mut recorder = Recorder::new(vec![]).unwrap();
+        recorder.record(&score, &registry).unwrap();
+        let data = recorder.into_inner();
+
+        let unregistered = Registry::new();
+        let err = read_records(&data[..], &unregistered).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("is not registered"));
+
+        let mut recorder = Recorder::new(vec![]).unwrap();
+        let len = recorder.writer.len();
+        let err = recorder.record(&score, &unregistered).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(recorder.into_inner().len(), len);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Button, Event, Input};
+use crate::{keyboard::ModifierState, Button, Event, Input};
 
 /// Stores button state.
 #[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -19,13 +19,44 @@ pub struct ButtonArgs {
     /// An optional scancode that tells the physical layout of a keyboard key.
     /// For other devices than keyboard, this is set to `None`.
     ///
-    /// Scancode follows SDL (https://wiki.libsdl.org/SDL_Scancode).
+    /// Scancode follows SDL (<https://wiki.libsdl.org/SDL_Scancode>).
     ///
     /// This is stored here to make `Button` equality check work with keyboard layouts.
     ///
     /// Some window backends might not support scancodes.
-    /// To test a window backend, use https://github.com/PistonDevelopers/piston-examples/tree/master/user_input
+    /// To test a window backend, use <https://github.com/PistonDevelopers/piston-examples/tree/master/user_input>
     pub scancode: Option<i32>,
+    /// Whether this press was generated by holding down the button.
+    ///
+    /// Operating systems repeat key presses while a key is held down.
+    /// For releases and other devices than keyboard, this is set to `false`.
+    ///
+    /// Some window backends might not report repeated presses at all.
+    /// Use [`KeyRepeat`](crate::keyboard::KeyRepeat) for consistent behavior across backends.
+    #[serde(default)]
+    pub repeat: bool,
+    /// An optional snapshot of the modifier keys after this button event.
+    ///
+    /// This is set by window backends that know the modifier state,
+    /// or by [`ModifierState::attach`].
+    #[serde(default)]
+    pub modifiers: Option<ModifierState>,
+}
+
+impl ButtonArgs {
+    /// Create a new `ButtonArgs` object. Intended for use by backends when
+    /// emitting events.
+    ///
+    /// The press is not marked as repeated and has no modifier snapshot.
+    pub fn new(state: ButtonState, button: Button, scancode: Option<i32>) -> Self {
+        ButtonArgs {
+            state,
+            button,
+            scancode,
+            repeat: false,
+            modifiers: None,
+        }
+    }
 }
 
 /// Changed button state.
@@ -68,7 +99,7 @@ impl ButtonEvent for Event {
 pub trait PressEvent: Sized {
     /// Creates a press event.
     ///
-    /// Preserves scancode from original button event, if any.
+    /// Preserves scancode and modifier snapshot from original button event, if any.
     /// Preserves time stamp from original input event, if any.
     fn from_button(button: Button, old_event: &Self) -> Option<Self>;
     /// Calls closure if this is a press event.
@@ -92,11 +123,7 @@ where
             ButtonEvent::from_button_args(args, old_event)
         } else {
             ButtonEvent::from_button_args(
-                ButtonArgs {
-                    state: ButtonState::Press,
-                    button,
-                    scancode: None,
-                },
+                ButtonArgs::new(ButtonState::Press, button, None),
                 old_event,
             )
         }
@@ -123,8 +150,9 @@ where
 pub trait ReleaseEvent: Sized {
     /// Creates a release event.
     ///
-    /// Preserves scancode from original button event, if any.
+    /// Preserves scancode and modifier snapshot from original button event, if any.
     /// Preserves time stamp from original input event, if any.
+    /// A release is never marked as repeated.
     fn from_button(button: Button, old_event: &Self) -> Option<Self>;
     /// Calls closure if this is a release event.
     fn release<U, F>(&self, f: F) -> Option<U>
@@ -144,14 +172,11 @@ where
         if let Some(mut args) = old_event.button_args() {
             args.state = ButtonState::Release;
             args.button = button;
+            args.repeat = false;
             ButtonEvent::from_button_args(args, old_event)
         } else {
             ButtonEvent::from_button_args(
-                ButtonArgs {
-                    state: ButtonState::Release,
-                    button,
-                    scancode: None,
-                },
+                ButtonArgs::new(ButtonState::Release, button, None),
                 old_event,
             )
         }
@@ -186,6 +211,8 @@ mod tests {
             state: ButtonState::Press,
             button: Key::S.into(),
             scancode: None,
+            repeat: false,
+            modifiers: None,
         }
         .into();
         let button = Button::Keyboard(Key::A);
@@ -206,6 +233,8 @@ mod tests {
             state: ButtonState::Release,
             button: Key::S.into(),
             scancode: None,
+            repeat: false,
+            modifiers: None,
         }
         .into();
         let button = Button::Keyboard(Key::A);
@@ -0,0 +1,359 @@
+//! Coalescing of high-frequency motion events.
+//!
+//! Mice and tablets with a high polling rate might deliver hundreds of motion events per frame.
+//! A [`Coalescer`] merges these events before they are handled:
+//!
+//! - Cursor moves are merged, keeping the latest position
+//! - Relative motion and scroll deltas are summed
+//! - Touch moves are merged per finger
+//!
+//! Motion of different kinds, e.g. absolute and relative mouse motion, might be interleaved.
+//! Other events are kept in order and prevent merging across them,
+//! such that e.g. a button press happens at the same position as before.
+//!
+//! Applications that need every position, e.g. drawing applications,
+//! can keep the history of the original events merged into each event.
+//!
+//! ```ignore
+//! let mut coalescer = Coalescer::new(true);
+//! for e in polled_events {
+//!     coalescer.push(e);
+//! }
+//! while let Some(e) = coalescer.pop() {
+//!     for original in coalescer.history() {
+//!         canvas.draw(original);
+//!     }
+//! }
+//! ```
+
+use std::collections::VecDeque;
+
+use crate::{Event, GesturePhase, Input, Motion, ScrollArgs, ScrollUnit, Touch};
+
+/// The kind of motion that can be merged.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Kind {
+    Cursor,
+    Relative,
+    MouseScroll,
+    Scroll {
+        unit: ScrollUnit,
+        precise: bool,
+        momentum: Option<GesturePhase>,
+    },
+    Touch {
+        device: i64,
+        id: i64,
+    },
+}
+
+fn kind(e: &Event) -> Option<Kind> {
+    match *e {
+        Event::Input(Input::Move(ref motion), _) => match *motion {
+            Motion::MouseCursor(_) => Some(Kind::Cursor),
+            Motion::MouseRelative(_) => Some(Kind::Relative),
+            Motion::MouseScroll(_) => Some(Kind::MouseScroll),
+            // The start and end of momentum scrolling are kept.
+            Motion::Scroll(ScrollArgs {
+                unit,
+                precise,
+                momentum: momentum @ (None | Some(GesturePhase::Update)),
+                ..
+            }) => Some(Kind::Scroll {
+                unit,
+                precise,
+                momentum,
+            }),
+            Motion::Touch(ref args) if args.touch == Touch::Move => Some(Kind::Touch {
+                device: args.device,
+                id: args.id,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn sum(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+/// Merges two events of the same kind, using the time stamp of the latest.
+fn merge(a: &Event, b: &Event) -> Event {
+    let (a, (b, time_stamp)) = match (a, b) {
+        (Event::Input(Input::Move(a), _), Event::Input(Input::Move(b), time_stamp)) => {
+            (a, (b, *time_stamp))
+        }
+        _ => return b.clone(),
+    };
+    let motion = match (*a, *b) {
+        (Motion::MouseRelative(a), Motion::MouseRelative(b)) => Motion::MouseRelative(sum(a, b)),
+        (Motion::MouseScroll(a), Motion::MouseScroll(b)) => Motion::MouseScroll(sum(a, b)),
+        (Motion::Scroll(a), Motion::Scroll(b)) => Motion::Scroll(ScrollArgs {
+            delta: sum(a.delta, b.delta),
+            ..b
+        }),
+        (_, b) => b,
+    };
+    Event::Input(Input::Move(motion), time_stamp)
+}
+
+/// Returns `true` if the event is motion that can be merged with other events.
+pub fn can_coalesce(e: &Event) -> bool {
+    kind(e).is_some()
+}
+
+/// An event waiting in the queue.
+#[derive(Clone, Debug)]
+struct Entry {
+    event: Event,
+    kind: Option<Kind>,
+    history: Vec<Event>,
+}
+
+/// Merges consecutive motion events.
+///
+/// Events are pushed in the order they were received,
+/// and popped in the same order after merging.
+#[derive(Clone, Debug)]
+pub struct Coalescer {
+    keep_history: bool,
+    queue: VecDeque<Entry>,
+    history: Vec<Event>,
+}
+
+impl Coalescer {
+    /// Creates a new coalescer.
+    ///
+    /// When `keep_history` is `true`, the original motion events are kept.
+    pub fn new(keep_history: bool) -> Coalescer {
+        Coalescer {
+            keep_history,
+            queue: VecDeque::new(),
+            history: vec![],
+        }
+    }
+
+    /// Returns whether the original motion events are kept.
+    pub fn keep_history(&self) -> bool {
+        self.keep_history
+    }
+
+    /// Sets whether the original motion events are kept.
+    pub fn set_keep_history(&mut self, enable: bool) {
+        self.keep_history = enable;
+    }
+
+    /// Adds an event, merging it with a previous event of the same kind.
+    ///
+    /// Motion is merged across motion of other kinds, e.g. touch moves of other fingers,
+    /// but not across other events.
+    pub fn push(&mut self, e: Event) {
+        let kind = kind(&e);
+        if let Some(kind) = kind {
+            for entry in self.queue.iter_mut().rev() {
+                if entry.kind == Some(kind) {
+                    entry.event = merge(&entry.event, &e);
+                    if self.keep_history {
+                        entry.history.push(e);
+                    }
+                    return;
+                }
+                // Motion of other kinds does not depend on this motion,
+                // e.g. backends might report absolute and relative motion for each move.
+                if entry.kind.is_none() {
+                    break;
+                }
+            }
+        }
+        let history = if self.keep_history && kind.is_some() {
+            vec![e.clone()]
+        } else {
+            vec![]
+        };
+        self.queue.push_back(Entry {
+            event: e,
+            kind,
+            history,
+        });
+    }
+
+    /// Removes the next event.
+    ///
+    /// Replaces the history with the original events merged into this event.
+    pub fn pop(&mut self) -> Option<Event> {
+        let entry = self.queue.pop_front()?;
+        self.history = entry.history;
+        Some(entry.event)
+    }
+
+    /// Returns the original events merged into the last popped event, in order.
+    ///
+    /// This is empty when history is disabled, or when the last event was not motion.
+    pub fn history(&self) -> &[Event] {
+        &self.history
+    }
+
+    /// Returns the number of events waiting to be popped.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if there are no events waiting to be popped.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Removes all events and the history.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.history.clear();
+    }
+}
+
+impl Default for Coalescer {
+    fn default() -> Coalescer {
+        Coalescer::new(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Button, ButtonArgs, ButtonState, MouseButton, TouchArgs};
+
+    fn motion(motion: Motion, time: u32) -> Event {
+        Event::Input(Input::Move(motion), Some(time))
+    }
+
+    fn touch(id: i64, pos: [f64; 2], touch: Touch, time: u32) -> Event {
+        motion(Motion::Touch(TouchArgs::new(0, id, pos, 1.0, touch)), time)
+    }
+
+    fn press(time: u32) -> Event {
+        let args = ButtonArgs::new(ButtonState::Press, Button::Mouse(MouseButton::Left), None);
+        Event::Input(Input::Button(args), Some(time))
+    }
+
+    fn drain(coalescer: &mut Coalescer) -> Vec<Event> {
+        std::iter::from_fn(|| coalescer.pop()).collect()
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut coalescer = Coalescer::new(false);
+        coalescer.push(motion(Motion::MouseCursor([1.0, 1.0]), 1));
+        coalescer.push(motion(Motion::MouseCursor([2.0, 3.0]), 2));
+        coalescer.push(motion(Motion::MouseCursor([4.0, 5.0]), 3));
+        coalescer.push(press(4));
+        coalescer.push(motion(Motion::MouseCursor([6.0, 7.0]), 5));
+        let events = drain(&mut coalescer);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0], motion(Motion::MouseCursor([4.0, 5.0]), 3));
+        assert_eq!(events[1], press(4));
+        assert_eq!(events[2], motion(Motion::MouseCursor([6.0, 7.0]), 5));
+        assert!(coalescer.history().is_empty());
+    }
+
+    #[test]
+    fn test_deltas() {
+        let mut coalescer = Coalescer::new(false);
+        coalescer.push(motion(Motion::MouseRelative([1.0, 2.0]), 1));
+        coalescer.push(motion(Motion::MouseRelative([3.0, -1.0]), 2));
+        coalescer.push(motion(Motion::MouseScroll([0.0, 1.0]), 3));
+        coalescer.push(motion(Motion::MouseScroll([0.0, 2.0]), 4));
+        let pixels = ScrollArgs::new([0.0, 10.0], ScrollUnit::Pixels);
+        let lines = ScrollArgs::new([0.0, 1.0], ScrollUnit::Lines);
+        coalescer.push(motion(Motion::Scroll(pixels), 5));
+        coalescer.push(motion(Motion::Scroll(pixels), 6));
+        coalescer.push(motion(Motion::Scroll(lines), 7));
+        let events = drain(&mut coalescer);
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], motion(Motion::MouseRelative([4.0, 1.0]), 2));
+        assert_eq!(events[1], motion(Motion::MouseScroll([0.0, 3.0]), 4));
+        let merged = ScrollArgs::new([0.0, 20.0], ScrollUnit::Pixels);
+        assert_eq!(events[2], motion(Motion::Scroll(merged), 6));
+        assert_eq!(events[3], motion(Motion::Scroll(lines), 7));
+    }
+
+    #[test]
+    fn test_interleaved() {
+        let mut coalescer = Coalescer::new(true);
+        for i in 0..3 {
+            coalescer.push(motion(Motion::MouseCursor([i as f64, 0.0]), 2 * i));
+            coalescer.push(motion(Motion::MouseRelative([1.0, 0.0]), 2 * i + 1));
+        }
+        coalescer.push(press(6));
+        coalescer.push(motion(Motion::MouseCursor([3.0, 0.0]), 7));
+        let events = drain(&mut coalescer);
+        assert_eq!(
+            events,
+            vec![
+                motion(Motion::MouseCursor([2.0, 0.0]), 4),
+                motion(Motion::MouseRelative([3.0, 0.0]), 5),
+                press(6),
+                motion(Motion::MouseCursor([3.0, 0.0]), 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_momentum() {
+        let mut coalescer = Coalescer::new(false);
+        let mut args = ScrollArgs::new([0.0, 1.0], ScrollUnit::Pixels);
+        for phase in &[
+            GesturePhase::Start,
+            GesturePhase::Start,
+            GesturePhase::Update,
+        ] {
+            args.momentum = Some(*phase);
+            coalescer.push(motion(Motion::Scroll(args), 1));
+        }
+        coalescer.push(motion(Motion::Scroll(args), 2));
+        let events = drain(&mut coalescer);
+        assert_eq!(events.len(), 3);
+        args.delta = [0.0, 2.0];
+        assert_eq!(events[2], motion(Motion::Scroll(args), 2));
+    }
+
+    #[test]
+    fn test_touch() {
+        let mut coalescer = Coalescer::new(false);
+        coalescer.push(touch(0, [0.1, 0.1], Touch::Start, 1));
+        coalescer.push(touch(0, [0.2, 0.2], Touch::Move, 2));
+        coalescer.push(touch(1, [0.5, 0.5], Touch::Move, 3));
+        coalescer.push(touch(0, [0.3, 0.3], Touch::Move, 4));
+        coalescer.push(touch(1, [0.6, 0.6], Touch::Move, 5));
+        coalescer.push(touch(0, [0.3, 0.3], Touch::End, 6));
+        coalescer.push(touch(1, [0.7, 0.7], Touch::Move, 7));
+        let events = drain(&mut coalescer);
+        assert_eq!(
+            events,
+            vec![
+                touch(0, [0.1, 0.1], Touch::Start, 1),
+                touch(0, [0.3, 0.3], Touch::Move, 4),
+                touch(1, [0.6, 0.6], Touch::Move, 5),
+                touch(0, [0.3, 0.3], Touch::End, 6),
+                touch(1, [0.7, 0.7], Touch::Move, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let mut coalescer = Coalescer::new(true);
+        let moves: Vec<Event> = (0..4)
+            .map(|i| motion(Motion::MouseCursor([i as f64, 0.0]), i))
+            .collect();
+        for e in &moves {
+            coalescer.push(e.clone());
+        }
+        coalescer.push(press(4));
+        assert_eq!(coalescer.len(), 2);
+        assert_eq!(coalescer.pop(), Some(moves[3].clone()));
+        assert_eq!(coalescer.history(), &moves[..]);
+        assert_eq!(coalescer.pop(), Some(press(4)));
+        assert!(coalescer.history().is_empty());
+        assert_eq!(coalescer.pop(), None);
+    }
+}
@@ -0,0 +1,1042 @@
+//! Compact binary encoding of event streams.
+//!
+//! A stream starts with a header of the magic bytes [`MAGIC`]
+//! followed by a format version byte, see [`CODEC_VERSION`].
+//! Each event is written as a varint tag telling the kind of event
+//! and whether it has a time stamp,
+//! followed by the time stamp relative to the last time stamp in the stream,
+//! and the event arguments.
+//!
+//! Event arguments are encoded without field names:
+//!
+//! - Integers are varints, using zig-zag encoding for signed integers
+//! - Enum variants are encoded by their index as varints
+//! - Floats are little endian
+//! - Strings and sequences are prefixed with their length
+//!
+//! Since enum variants are encoded by index,
+//! the order of variants is part of the format.
+//!
+//! Custom events are encoded by their event id and the arguments
+//! serialized by the functions of a [`Registry`],
+//! passed with [`Encoder::with_registry`] and [`Decoder::with_registry`].
+//! Encoding or decoding an unregistered custom event returns an error.
+//!
+//! ```ignore
+//! let mut encoder = Encoder::with_registry(vec![], registry.clone())?;
+//! encoder.encode(&e)?;
+//! let data = encoder.into_inner();
+//! let mut decoder = Decoder::with_registry(&data[..], registry)?;
+//! while let Some(e) = decoder.decode()? {
+//!     ...
+//! }
+//! ```
+
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    io::{self, Read, Write},
+};
+
+use serde::{de, ser, Deserialize, Serialize};
+
+use crate::{custom::Registry, event_id::EventId, Event, TimeStamp};
+
+/// The magic bytes at the start of an encoded stream.
+pub const MAGIC: [u8; 4] = *b"PIEV";
+/// The version of the format written by [`Encoder`].
+pub const CODEC_VERSION: u8 = 2;
+/// The maximum length of strings and sequences accepted when decoding.
+pub const MAX_LEN: usize = 1 << 24;
+
+const KIND_INPUT: u64 = 0;
+const KIND_LOOP: u64 = 1;
+const KIND_CUSTOM: u64 = 2;
+
+/// An error when encoding or decoding events.
+#[derive(Debug)]
+pub enum CodecError {
+    /// An I/O error.
+    Io(io::Error),
+    /// The stream does not start with the magic bytes.
+    InvalidMagic,
+    /// The format version is not supported.
+    UnsupportedVersion(u8),
+    /// A custom event is not registered.
+    CustomEvent(EventId),
+    /// The data is malformed.
+    InvalidData(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CodecError::Io(ref err) => write!(f, "{}", err),
+            CodecError::InvalidMagic => write!(f, "Not an encoded event stream"),
+            CodecError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported event stream version {}, expected {}",
+                version, CODEC_VERSION
+            ),
+            CodecError::CustomEvent(id) => {
+                write!(f, "Custom event `{}` is not registered", id.0)
+            }
+            CodecError::InvalidData(ref msg) => write!(f, "Invalid data: {}", msg),
+        }
+    }
+}
+
+impl Error for CodecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            CodecError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CodecError {
+    fn from(err: io::Error) -> CodecError {
+        CodecError::Io(err)
+    }
+}
+
+impl ser::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CodecError::InvalidData(msg.to_string())
+    }
+}
+
+impl de::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CodecError::InvalidData(msg.to_string())
+    }
+}
+
+fn invalid<T>(msg: &str) -> Result<T, CodecError> {
+    Err(CodecError::InvalidData(msg.into()))
+}
+
+/// Writes an event stream.
+#[derive(Debug)]
+pub struct Encoder<W: Write> {
+    writer: Writer<W>,
+    last_timestamp: TimeStamp,
+    registry: Registry,
+}
+
+impl<W: Write> Encoder<W> {
+    /// Creates a new encoder without custom events, writing the stream header.
+    pub fn new(writer: W) -> Result<Self, CodecError> {
+        Encoder::with_registry(writer, Registry::new())
+    }
+
+    /// Creates a new encoder using a registry for custom events, writing the stream header.
+    pub fn with_registry(mut writer: W, registry: Registry) -> Result<Self, CodecError> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[CODEC_VERSION])?;
+        Ok(Encoder {
+            writer: Writer { w: writer },
+            last_timestamp: 0,
+            registry,
+        })
+    }
+
+    /// Returns the registry for custom events.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Encodes an event.
+    ///
+    /// Returns an error for unregistered custom events.
+    pub fn encode(&mut self, e: &Event) -> Result<(), CodecError> {
+        let (kind, timestamp) = match *e {
+            Event::Input(_, timestamp) => (KIND_INPUT, timestamp),
+            Event::Loop(_) => (KIND_LOOP, None),
+            Event::Custom(id, _, _) if !self.registry.is_registered(id) => {
+                return Err(CodecError::CustomEvent(id))
+            }
+            Event::Custom(_, _, timestamp) => (KIND_CUSTOM, timestamp),
+        };
+        self.writer
+            .varint((kind << 1) | timestamp.is_some() as u64)?;
+        if let Some(timestamp) = timestamp {
+            self.writer
+                .zigzag(timestamp as i64 - self.last_timestamp as i64)?;
+            self.last_timestamp = timestamp;
+        }
+        match *e {
+            Event::Input(ref input, _) => input.serialize(&mut self.writer),
+            Event::Loop(ref args) => args.serialize(&mut self.writer),
+            Event::Custom(id, ref args, _) => {
+                let data = self
+                    .registry
+                    .serialize_custom(id, &**args)
+                    .map_err(CodecError::InvalidData)?;
+                id.0.serialize(&mut self.writer)?;
+                data.serialize(&mut self.writer)
+            }
+        }
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<(), CodecError> {
+        Ok(self.writer.w.flush()?)
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer.w
+    }
+}
+
+/// Reads an event stream.
+#[derive(Debug)]
+pub struct Decoder<R: Read> {
+    reader: Reader<R>,
+    last_timestamp: TimeStamp,
+    registry: Registry,
+}
+
+impl<R: Read> Decoder<R> {
+    /// Creates a new decoder without custom events, reading the stream header.
+    pub fn new(reader: R) -> Result<Self, CodecError> {
+        Decoder::with_registry(reader, Registry::new())
+    }
+
+    /// Creates a new decoder using a registry for custom events, reading the stream header.
+    pub fn with_registry(mut reader: R, registry: Registry) -> Result<Self, CodecError> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(CodecError::InvalidMagic);
+        }
+        if header[4] != CODEC_VERSION {
+            return Err(CodecError::UnsupportedVersion(header[4]));
+        }
+        Ok(Decoder {
+            reader: Reader { r: reader },
+            last_timestamp: 0,
+            registry,
+        })
+    }
+
+    /// Returns the registry for custom events.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Decodes the next event.
+    ///
+    /// Returns `Ok(None)` at the end of the stream.
+    pub fn decode(&mut self) -> Result<Option<Event>, CodecError> {
+        let mut first = [0];
+        loop {
+            match self.reader.r.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let tag = self.reader.varint_from(first[0])?;
+        let timestamp = if tag & 1 == 1 {
+            let delta = self.reader.zigzag()?;
+            let timestamp = match (self.last_timestamp as i64).checked_add(delta) {
+                Some(x) => convert(x)?,
+                None => return invalid("time stamp out of range"),
+            };
+            self.last_timestamp = timestamp;
+            Some(timestamp)
+        } else {
+            None
+        };
+        match tag >> 1 {
+            KIND_INPUT => Ok(Some(Event::Input(
+                Deserialize::deserialize(&mut self.reader)?,
+                timestamp,
+            ))),
+            KIND_LOOP if timestamp.is_none() => Ok(Some(Event::Loop(Deserialize::deserialize(
+                &mut self.reader,
+            )?))),
+            KIND_CUSTOM => {
+                let name: String = Deserialize::deserialize(&mut self.reader)?;
+                let data: Vec<u8> = Deserialize::deserialize(&mut self.reader)?;
+                let (id, args) = self
+                    .registry
+                    .deserialize_custom(&name, &data)
+                    .map_err(CodecError::InvalidData)?;
+                Ok(Some(Event::Custom(id, args, timestamp)))
+            }
+            _ => invalid("unknown event kind"),
+        }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader.r
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = Result<Event, CodecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decode().transpose()
+    }
+}
+
+/// Encodes a value, e.g. `Input` or `Loop`, without stream header.
+pub fn encode_value<T, W>(writer: W, value: &T) -> Result<(), CodecError>
+where
+    T: Serialize,
+    W: Write,
+{
+    value.serialize(&mut Writer { w: writer })
+}
+
+/// Decodes a value, e.g. `Input` or `Loop`, without stream header.
+pub fn decode_value<T, R>(reader: R) -> Result<T, CodecError>
+where
+    T: de::DeserializeOwned,
+    R: Read,
+{
+    T::deserialize(&mut Reader { r: reader })
+}
+
+#[derive(Debug)]
+struct Writer<W> {
+    w: W,
+}
+
+impl<W: Write> Writer<W> {
+    fn varint(&mut self, mut v: u64) -> Result<(), CodecError> {
+        loop {
+            let b = (v & 0x7f) as u8;
+            v >>= 7;
+            if v == 0 {
+                self.w.write_all(&[b])?;
+                return Ok(());
+            }
+            self.w.write_all(&[b | 0x80])?;
+        }
+    }
+
+    fn zigzag(&mut self, v: i64) -> Result<(), CodecError> {
+        self.varint(((v << 1) ^ (v >> 63)) as u64)
+    }
+
+    fn len(&mut self, len: Option<usize>) -> Result<(), CodecError> {
+        match len {
+            Some(len) => self.varint(len as u64),
+            None => invalid("sequence length must be known"),
+        }
+    }
+}
+
+impl<W: Write> ser::Serializer for &mut Writer<W> {
+    type Ok = ();
+    type Error = CodecError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<(), CodecError> {
+        Ok(self.w.write_all(&[v as u8])?)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CodecError> {
+        self.zigzag(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CodecError> {
+        self.zigzag(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CodecError> {
+        self.zigzag(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CodecError> {
+        self.zigzag(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CodecError> {
+        Ok(self.w.write_all(&[v])?)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CodecError> {
+        self.varint(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CodecError> {
+        self.varint(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CodecError> {
+        self.varint(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), CodecError> {
+        Ok(self.w.write_all(&v.to_le_bytes())?)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), CodecError> {
+        Ok(self.w.write_all(&v.to_le_bytes())?)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), CodecError> {
+        self.varint(v as u64)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CodecError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CodecError> {
+        self.varint(v.len() as u64)?;
+        Ok(self.w.write_all(v)?)
+    }
+
+    fn serialize_none(self) -> Result<(), CodecError> {
+        self.serialize_u8(0)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), CodecError> {
+        self.serialize_u8(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+    ) -> Result<(), CodecError> {
+        self.varint(index.into())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        self.varint(index.into())?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, CodecError> {
+        self.len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, CodecError> {
+        self.varint(index.into())?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, CodecError> {
+        self.len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, CodecError> {
+        self.varint(index.into())?;
+        Ok(self)
+    }
+}
+
+macro_rules! impl_serialize_compound {
+    ($($trait:ident, $method:ident;)*) => {
+        $(
+            impl<W: Write> ser::$trait for &mut Writer<W> {
+                type Ok = ();
+                type Error = CodecError;
+
+                fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), CodecError> {
+                    value.serialize(&mut **self)
+                }
+
+                fn end(self) -> Result<(), CodecError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_compound! {
+    SerializeSeq, serialize_element;
+    SerializeTuple, serialize_element;
+    SerializeTupleStruct, serialize_field;
+    SerializeTupleVariant, serialize_field;
+}
+
+impl<W: Write> ser::SerializeMap for &mut Writer<W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), CodecError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for &mut Writer<W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for &mut Writer<W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Reader<R> {
+    r: R,
+}
+
+impl<R: Read> Reader<R> {
+    fn byte(&mut self) -> Result<u8, CodecError> {
+        let mut buf = [0];
+        self.r.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, CodecError> {
+        let first = self.byte()?;
+        self.varint_from(first)
+    }
+
+    fn varint_from(&mut self, first: u8) -> Result<u64, CodecError> {
+        let mut v = (first & 0x7f) as u64;
+        let mut b = first;
+        let mut shift = 0;
+        while b & 0x80 != 0 {
+            shift += 7;
+            b = self.byte()?;
+            if shift == 63 && b > 1 {
+                return invalid("varint overflow");
+            }
+            v |= ((b & 0x7f) as u64) << shift;
+        }
+        Ok(v)
+    }
+
+    fn zigzag(&mut self) -> Result<i64, CodecError> {
+        let v = self.varint()?;
+        Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+    }
+
+    fn len(&mut self) -> Result<usize, CodecError> {
+        match usize::try_from(self.varint()?) {
+            Ok(len) if len <= MAX_LEN => Ok(len),
+            _ => invalid("length too large"),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, CodecError> {
+        let len = self.len()?;
+        let mut buf = vec![];
+        (&mut self.r).take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(buf)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CodecError> {
+        let mut buf = [0; N];
+        self.r.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+fn convert<T, U>(v: T) -> Result<U, CodecError>
+where
+    U: TryFrom<T>,
+{
+    U::try_from(v).or_else(|_| invalid("integer out of range"))
+}
+
+impl<'de, R: Read> de::Deserializer<'de> for &mut Reader<R> {
+    type Error = CodecError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        invalid("format is not self-describing")
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match self.byte()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            _ => invalid("invalid bool"),
+        }
+    }
+
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i8(convert(self.zigzag()?)?)
+    }
+
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i16(convert(self.zigzag()?)?)
+    }
+
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i32(convert(self.zigzag()?)?)
+    }
+
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i64(self.zigzag()?)
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u8(self.byte()?)
+    }
+
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u16(convert(self.varint()?)?)
+    }
+
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u32(convert(self.varint()?)?)
+    }
+
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u64(self.varint()?)
+    }
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_f32(f32::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_f64(f64::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match std::char::from_u32(convert(self.varint()?)?) {
+            Some(c) => visitor.visit_char(c),
+            None => invalid("invalid char"),
+        }
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match String::from_utf8(self.bytes()?) {
+            Ok(s) => visitor.visit_string(s),
+            Err(_) => invalid("invalid UTF-8"),
+        }
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_byte_buf(self.bytes()?)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match self.byte()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            _ => invalid("invalid option"),
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let len = self.len()?;
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let len = self.len()?;
+        visitor.visit_map(Access { de: self, len })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_seq(Access {
+            de: self,
+            len: fields.len(),
+        })
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        invalid("format is not self-describing")
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        invalid("format is not self-describing")
+    }
+}
+
+/// Reads a known number of elements.
+struct Access<'a, R> {
+    de: &'a mut Reader<R>,
+    len: usize,
+}
+
+impl<'de, 'a, R: Read> de::SeqAccess<'de> for Access<'a, R> {
+    type Error = CodecError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, CodecError> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, 'a, R: Read> de::MapAccess<'de> for Access<'a, R> {
+    type Error = CodecError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, CodecError> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, CodecError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, R: Read> de::EnumAccess<'de> for &mut Reader<R> {
+    type Error = CodecError;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), CodecError> {
+        let index: u32 = convert(self.varint()?)?;
+        let value = seed.deserialize(de::value::U32Deserializer::<CodecError>::new(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, R: Read> de::VariantAccess<'de> for &mut Reader<R> {
+    type Error = CodecError;
+
+    fn unit_variant(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, CodecError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{
+        keyboard::ModifierState, Button, ButtonArgs, ButtonState, FileDrag, GenericEvent, Input,
+        Key, Loop, Motion, RenderArgs, Touch, TouchArgs, UpdateArgs,
+    };
+
+    fn events() -> Vec<Event> {
+        let mut button = ButtonArgs::new(ButtonState::Press, Button::Keyboard(Key::A), Some(30));
+        button.modifiers = Some(ModifierState::new());
+        vec![
+            Event::Input(Input::Button(button), Some(1000)),
+            Event::Input(Input::Move(Motion::MouseCursor([1.5, -2.0])), Some(1016)),
+            Event::Input(
+                Input::Move(Motion::Touch(TouchArgs::new(
+                    1,
+                    2,
+                    [0.25, 0.5],
+                    1.0,
+                    Touch::Move,
+                ))),
+                Some(1010),
+            ),
+            Event::Input(Input::Text("héllo".into()), None),
+            Event::Input(
+                Input::FileDrag(FileDrag::Drop(PathBuf::from("a.txt"))),
+                None,
+            ),
+            UpdateArgs { dt: 0.5 }.into(),
+            RenderArgs {
+                ext_dt: 0.0,
+                window_size: [100.0, 100.0],
+                draw_size: [200, 200],
+            }
+            .into(),
+        ]
+    }
+
+    fn encode(events: &[Event]) -> Vec<u8> {
+        let mut encoder = Encoder::new(vec![]).unwrap();
+        for e in events {
+            encoder.encode(e).unwrap();
+        }
+        encoder.into_inner()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let events = events();
+        let data = encode(&events);
+        let decoded: Vec<Event> = Decoder::new(&data[..])
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, events);
+        let timestamps: Vec<_> = decoded.iter().map(|e| e.time_stamp()).collect();
+        let expected: Vec<_> = events.iter().map(|e| e.time_stamp()).collect();
+        assert_eq!(timestamps, expected);
+    }
+
+    #[test]
+    fn test_value() {
+        let mut data = vec![];
+        encode_value(&mut data, &Loop::Update(UpdateArgs { dt: 0.25 })).unwrap();
+        let value: Loop = decode_value(&data[..]).unwrap();
+        assert_eq!(value, Loop::Update(UpdateArgs { dt: 0.25 }));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            Decoder::new(&b"PIEX\x01"[..]),
+            Err(CodecError::InvalidMagic)
+        ));
+        assert!(matches!(
+            Decoder::new(&b"PIEV\x01"[..]),
+            Err(CodecError::UnsupportedVersion(1))
+        ));
+        let custom = Event::Custom(EventId("test"), std::sync::Arc::new(()), None);
+        assert!(matches!(
+            Encoder::new(vec![]).unwrap().encode(&custom),
+            Err(CodecError::CustomEvent(_))
+        ));
+    }
+
+    #[test]
+    fn test_fuzz_decoder() {
+        let data = encode(&events());
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut rand = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for _ in 0..10_000 {
+            let mut fuzzed = data.clone();
+            match rand() % 3 {
+                0 => {
+                    let n = 1 + rand() as usize % 4;
+                    for _ in 0..n {
+                        let i = MAGIC.len() + 1 + rand() as usize % (fuzzed.len() - 5);
+                        fuzzed[i] = rand() as u8;
+                    }
+                }
+                1 => fuzzed.truncate(rand() as usize % fuzzed.len()),
+                _ => {
+                    fuzzed.truncate(5);
+                    let n = rand() as usize % 64;
+                    fuzzed.extend((0..n).map(|_| rand() as u8));
+                }
+            }
+            if let Ok(decoder) = Decoder::new(&fuzzed[..]) {
+                for e in decoder {
+                    if e.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! Input contexts for layering user interfaces over gameplay.
+//!
+//! A [`ContextStack`] routes input events through layers of contexts, top first.
+//! Each context consumes some kinds of input, e.g. a pause menu consumes buttons and text,
+//! so contexts below it do not receive them.
+//! Events that are not input from the user, such as update or focus events,
+//! are passed to every context.
+//!
+//! Releases of buttons and ends of touches are sent to the contexts that received the press,
+//! even when a context above would consume them.
+//! When a context starts consuming input, e.g. when a menu is pushed,
+//! contexts below it receive releases of held buttons and cancels of held touches,
+//! similar to how [`ModifierKey::event`](crate::keyboard::ModifierKey::event)
+//! resets on focus loss.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    Button, ButtonArgs, ButtonEvent, ButtonState, GenericEvent, Touch, TouchArgs, TouchEvent,
+};
+
+bitflags!(
+    /// The kinds of input a context can consume.
+    #[derive(Default)]
+    pub struct InputKinds: u8 {
+        /// Keyboard, mouse and controller buttons.
+        const BUTTONS = 0b0001;
+        /// Text and text composition.
+        const TEXT = 0b0010;
+        /// Mouse, pen, scroll and controller axis motion.
+        const MOTION = 0b0100;
+        /// Touches and gestures.
+        const TOUCH = 0b1000;
+    }
+);
+
+/// Stores the state of a context.
+#[derive(Clone, Debug)]
+struct Layer<K> {
+    id: K,
+    consumes: InputKinds,
+    /// Held buttons with scancode.
+    buttons: HashMap<Button, Option<i32>>,
+    /// Held touches by device and id.
+    touches: HashMap<(i64, i64), TouchArgs>,
+}
+
+/// A synthesized event waiting to be sent.
+#[derive(Clone, Debug)]
+enum Pending<K> {
+    Release(K, Button, Option<i32>),
+    Cancel(K, TouchArgs),
+}
+
+/// A stack of input contexts.
+///
+/// The type `K` identifies contexts, e.g. an enum of gameplay and menus.
+#[derive(Clone, Debug)]
+pub struct ContextStack<K> {
+    layers: Vec<Layer<K>>,
+    pending: Vec<Pending<K>>,
+    /// Buttons released by synthesized events, whose physical release is ignored.
+    released: HashSet<Button>,
+    /// Touches cancelled by synthesized events, whose remaining events are ignored.
+    cancelled: HashSet<(i64, i64)>,
+}
+
+impl<K> Default for ContextStack<K>
+where
+    K: Copy + PartialEq,
+{
+    fn default() -> Self {
+        ContextStack::new()
+    }
+}
+
+impl<K> ContextStack<K>
+where
+    K: Copy + PartialEq,
+{
+    /// Creates a new empty context stack.
+    pub fn new() -> Self {
+        ContextStack {
+            layers: vec![],
+            pending: vec![],
+            released: HashSet::new(),
+            cancelled: HashSet::new(),
+        }
+    }
+
+    /// Pushes a context on top of the stack.
+    ///
+    /// Contexts below receive releases of held buttons and cancels of held touches
+    /// that the new context consumes, with the next event.
+    pub fn push(&mut self, id: K, consumes: InputKinds) {
+        self.layers.push(Layer {
+            id,
+            consumes,
+            buttons: HashMap::new(),
+            touches: HashMap::new(),
+        });
+        self.block();
+    }
+
+    /// Removes the context on top of the stack.
+    ///
+    /// The removed context receives releases of held buttons and cancels of held touches
+    /// with the next event.
+    pub fn pop(&mut self) -> Option<K> {
+        let mut layer = self.layers.pop()?;
+        self.release_all(&mut layer);
+        Some(layer.id)
+    }
+
+    /// Removes a context.
+    ///
+    /// Returns `true` if the context was in the stack.
+    pub fn remove(&mut self, id: K) -> bool {
+        match self.layers.iter().position(|layer| layer.id == id) {
+            Some(i) => {
+                let mut layer = self.layers.remove(i);
+                self.release_all(&mut layer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Changes the kinds of input a context consumes.
+    ///
+    /// Returns `true` if the context was in the stack.
+    pub fn set_consumes(&mut self, id: K, consumes: InputKinds) -> bool {
+        match self.layers.iter_mut().find(|layer| layer.id == id) {
+            Some(layer) => {
+                layer.consumes = consumes;
+                self.block();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the context on top of the stack.
+    pub fn top(&self) -> Option<K> {
+        self.layers.last().map(|layer| layer.id)
+    }
+
+    /// Returns the contexts, from top to bottom.
+    pub fn contexts(&self) -> impl Iterator<Item = K> + '_ {
+        self.layers.iter().rev().map(|layer| layer.id)
+    }
+
+    /// Returns `true` if a context holds a button pressed.
+    pub fn is_pressed(&self, id: K, button: Button) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| layer.id == id && layer.buttons.contains_key(&button))
+    }
+
+    fn release_all(&mut self, layer: &mut Layer<K>) {
+        for (button, scancode) in layer.buttons.drain() {
+            self.released.insert(button);
+            self.pending
+                .push(Pending::Release(layer.id, button, scancode));
+        }
+        for (key, args) in layer.touches.drain() {
+            self.cancelled.insert(key);
+            self.pending.push(Pending::Cancel(layer.id, args));
+        }
+    }
+
+    /// Releases buttons and touches in contexts below contexts that consume them.
+    fn block(&mut self) {
+        let mut blocked = InputKinds::empty();
+        for layer in self.layers.iter_mut().rev() {
+            if blocked.contains(InputKinds::BUTTONS) {
+                for (button, scancode) in layer.buttons.drain() {
+                    self.released.insert(button);
+                    self.pending
+                        .push(Pending::Release(layer.id, button, scancode));
+                }
+            }
+            if blocked.contains(InputKinds::TOUCH) {
+                for (key, args) in layer.touches.drain() {
+                    self.cancelled.insert(key);
+                    self.pending.push(Pending::Cancel(layer.id, args));
+                }
+            }
+            blocked |= layer.consumes;
+        }
+    }
+
+    /// Calls the closure for each context receiving an event, from top to bottom.
+    ///
+    /// Pending releases of buttons and cancels of touches are sent first,
+    /// preserving the time stamp of the event.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(K, &E),
+    {
+        if e.focus_args() == Some(false) {
+            let mut layers = std::mem::take(&mut self.layers);
+            for layer in layers.iter_mut().rev() {
+                self.release_all(layer);
+            }
+            self.layers = layers;
+        }
+        for pending in self.pending.drain(..) {
+            let ev = match pending {
+                Pending::Release(id, button, scancode) => {
+                    let args = ButtonArgs::new(ButtonState::Release, button, scancode);
+                    ButtonEvent::from_button_args(args, e).map(|ev| (id, ev))
+                }
+                Pending::Cancel(id, mut args) => {
+                    args.touch = Touch::Cancel;
+                    TouchEvent::from_touch_args(&args, e).map(|ev| (id, ev))
+                }
+            };
+            if let Some((id, ev)) = ev {
+                f(id, &ev);
+            }
+        }
+
+        if let Some(args) = e.button_args() {
+            let button = args.button;
+            let holders = self
+                .layers
+                .iter()
+                .any(|layer| layer.buttons.contains_key(&button));
+            match args.state {
+                ButtonState::Press if args.repeat => {
+                    // Repeats of a button no context holds are dropped,
+                    // since the press was released or never received.
+                    for layer in self.layers.iter().rev() {
+                        if layer.buttons.contains_key(&button) {
+                            f(layer.id, e);
+                        }
+                    }
+                }
+                ButtonState::Press => {
+                    self.released.remove(&button);
+                    for layer in self.layers.iter_mut().rev() {
+                        layer.buttons.insert(button, args.scancode);
+                        f(layer.id, e);
+                        if layer.consumes.contains(InputKinds::BUTTONS) {
+                            break;
+                        }
+                    }
+                }
+                ButtonState::Release if holders => {
+                    for layer in self.layers.iter_mut().rev() {
+                        if layer.buttons.remove(&button).is_some() {
+                            f(layer.id, e);
+                        }
+                    }
+                }
+                ButtonState::Release => {
+                    if !self.released.remove(&button) {
+                        self.route(InputKinds::BUTTONS, e, f);
+                    }
+                }
+            }
+        } else if let Some(args) = e.touch_args() {
+            let key = (args.device, args.id);
+            let holders = self
+                .layers
+                .iter()
+                .any(|layer| layer.touches.contains_key(&key));
+            match args.touch {
+                Touch::Start => {
+                    self.cancelled.remove(&key);
+                    for layer in self.layers.iter_mut().rev() {
+                        layer.touches.insert(key, args);
+                        f(layer.id, e);
+                        if layer.consumes.contains(InputKinds::TOUCH) {
+                            break;
+                        }
+                    }
+                }
+                _ if holders => {
+                    let end = args.touch != Touch::Move;
+                    for layer in self.layers.iter_mut().rev() {
+                        if let Some(held) = layer.touches.get_mut(&key) {
+                            *held = args;
+                            if end {
+                                layer.touches.remove(&key);
+                            }
+                            f(layer.id, e);
+                        }
+                    }
+                }
+                Touch::Move => {
+                    if !self.cancelled.contains(&key) {
+                        self.route(InputKinds::TOUCH, e, f);
+                    }
+                }
+                Touch::End | Touch::Cancel => {
+                    if !self.cancelled.remove(&key) {
+                        self.route(InputKinds::TOUCH, e, f);
+                    }
+                }
+            }
+        } else if e.text_args().is_some() || e.ime_args().is_some() {
+            self.route(InputKinds::TEXT, e, f);
+        } else if e.gesture_args().is_some() {
+            self.route(InputKinds::TOUCH, e, f);
+        } else if e.mouse_cursor_args().is_some()
+            || e.mouse_relative_args().is_some()
+            || e.mouse_scroll_args().is_some()
+            || e.controller_axis_args().is_some()
+            || e.pen_args().is_some()
+        {
+            self.route(InputKinds::MOTION, e, f);
+        } else {
+            self.route(InputKinds::empty(), e, f);
+        }
+    }
+
+    /// Sends an event from top to bottom until a context consumes it.
+    fn route<E, F>(&self, kind: InputKinds, e: &E, mut f: F)
+    where
+        F: FnMut(K, &E),
+    {
+        for layer in self.layers.iter().rev() {
+            f(layer.id, e);
+            if !kind.is_empty() && layer.consumes.contains(kind) {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Event, Input, Key, PressEvent, ReleaseEvent, UpdateArgs};
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum Context {
+        Game,
+        Menu,
+    }
+
+    fn button(state: ButtonState, key: Key) -> Event {
+        Input::Button(ButtonArgs::new(state, key.into(), None)).into()
+    }
+
+    fn run(stack: &mut ContextStack<Context>, e: &Event) -> Vec<(Context, String)> {
+        let mut out = vec![];
+        stack.event(e, |id, e| {
+            let s = if let Some(b) = e.press_args() {
+                format!("press {:?}", b)
+            } else if let Some(b) = e.release_args() {
+                format!("release {:?}", b)
+            } else {
+                format!("{:?}", e)
+            };
+            out.push((id, s))
+        });
+        out
+    }
+
+    #[test]
+    fn test_context_stack() {
+        use Context::*;
+
+        let w = Button::Keyboard(Key::W);
+        let mut stack = ContextStack::new();
+        stack.push(Game, InputKinds::all());
+        assert_eq!(
+            run(&mut stack, &button(ButtonState::Press, Key::W)),
+            vec![(Game, "press Keyboard(W)".into())]
+        );
+        assert!(stack.is_pressed(Game, w));
+
+        // Opening the menu releases the held key in gameplay.
+        stack.push(Menu, InputKinds::BUTTONS | InputKinds::TEXT);
+        let update: Event = UpdateArgs { dt: 0.1 }.into();
+        let out = run(&mut stack, &update);
+        assert_eq!(out[0], (Game, "release Keyboard(W)".into()));
+        assert_eq!(
+            out[1..].iter().map(|x| x.0).collect::<Vec<_>>(),
+            vec![Menu, Game]
+        );
+        assert!(!stack.is_pressed(Game, w));
+
+        // The physical release is ignored.
+        assert!(run(&mut stack, &button(ButtonState::Release, Key::W)).is_empty());
+
+        // The menu consumes buttons, but not mouse motion.
+        assert_eq!(
+            run(&mut stack, &button(ButtonState::Press, Key::Return)),
+            vec![(Menu, "press Keyboard(Return)".into())]
+        );
+        let cursor: Event = Input::Move(crate::Motion::MouseCursor([1.0, 2.0])).into();
+        assert_eq!(run(&mut stack, &cursor).len(), 2);
+
+        // Closing the menu releases the key held in the menu.
+        assert_eq!(stack.pop(), Some(Menu));
+        let out = run(&mut stack, &update);
+        assert_eq!(out[0], (Menu, "release Keyboard(Return)".into()));
+        assert!(run(&mut stack, &button(ButtonState::Release, Key::Return)).is_empty());
+    }
+
+    #[test]
+    fn test_context_release_to_holder() {
+        use Context::*;
+
+        let mut stack = ContextStack::new();
+        stack.push(Game, InputKinds::all());
+        stack.push(Menu, InputKinds::TEXT);
+        // The menu passes buttons through, so both contexts hold the key.
+        assert_eq!(
+            run(&mut stack, &button(ButtonState::Press, Key::A)).len(),
+            2
+        );
+        stack.set_consumes(Menu, InputKinds::BUTTONS);
+        let out = run(&mut stack, &button(ButtonState::Release, Key::A));
+        assert_eq!(out[0], (Game, "release Keyboard(A)".into()));
+        assert_eq!(out[1], (Menu, "release Keyboard(A)".into()));
+
+        // Focus loss releases held buttons in all contexts.
+        run(&mut stack, &button(ButtonState::Press, Key::B));
+        let out = run(&mut stack, &Input::Focus(false).into());
+        assert_eq!(out[0], (Menu, "release Keyboard(B)".into()));
+        assert_eq!(out.len(), 3);
+    }
+
+    #[test]
+    fn test_context_repeat_after_push() {
+        use Context::*;
+
+        let mut repeat = ButtonArgs::new(ButtonState::Press, Key::W.into(), None);
+        repeat.repeat = true;
+        let repeat: Event = Input::Button(repeat).into();
+
+        let mut stack = ContextStack::new();
+        stack.push(Game, InputKinds::all());
+        run(&mut stack, &button(ButtonState::Press, Key::W));
+        assert_eq!(
+            run(&mut stack, &repeat),
+            vec![(Game, "press Keyboard(W)".into())]
+        );
+
+        // Pushing the menu while the key auto-repeats does not start a press in the menu.
+        stack.push(Menu, InputKinds::BUTTONS);
+        let out = run(&mut stack, &repeat);
+        assert_eq!(out, vec![(Game, "release Keyboard(W)".into())]);
+        assert!(run(&mut stack, &repeat).is_empty());
+        assert!(!stack.is_pressed(Menu, Button::Keyboard(Key::W)));
+        assert!(run(&mut stack, &button(ButtonState::Release, Key::W)).is_empty());
+    }
+}
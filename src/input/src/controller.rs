@@ -1,6 +1,8 @@
 //! Back-end agnostic controller events.
 
-use crate::{Event, Input, Motion};
+use std::collections::HashMap;
+
+use crate::{Button, ButtonArgs, ButtonEvent, ButtonState, Event, GenericEvent, Input, Motion};
 
 /// Components of a controller button event. Not guaranteed consistent across
 /// backends.
@@ -60,6 +62,46 @@ impl ControllerAxisArgs {
     }
 }
 
+/// Whether a controller was added or removed.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum ConnectionState {
+    /// The controller was plugged in, or was present when the window was created.
+    Added,
+    /// The controller was removed.
+    Removed,
+}
+
+/// Components of a controller connection event.
+#[derive(Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct ControllerConnectionArgs {
+    /// Which controller was added or removed.
+    ///
+    /// This is the same id as used by button and axis events of the controller.
+    pub id: u32,
+    /// Whether the controller was added or removed.
+    pub state: ConnectionState,
+    /// The human readable name of the controller.
+    pub name: String,
+    /// An optional GUID identifying the controller model.
+    ///
+    /// GUID follows SDL, which can be used to look up gamepad mappings.
+    /// Some window backends might not support GUIDs.
+    pub guid: Option<String>,
+}
+
+impl ControllerConnectionArgs {
+    /// Create a new `ControllerConnectionArgs` object. Intended for use by backends when
+    /// emitting events.
+    pub fn new(id: u32, state: ConnectionState, name: String, guid: Option<String>) -> Self {
+        ControllerConnectionArgs {
+            id,
+            state,
+            name,
+            guid,
+        }
+    }
+}
+
 /// The position of a controller axis changed.
 pub trait ControllerAxisEvent: Sized {
     /// Creates a controller axis event.
@@ -100,6 +142,313 @@ impl ControllerAxisEvent for Event {
     }
 }
 
+crate::event_trait! {
+    /// A controller was added or removed.
+    pub trait ControllerConnectionEvent {
+        args: &ControllerConnectionArgs,
+        from_args: from_controller_connection_args,
+        accessor: controller_connection,
+        args_getter: controller_connection_args,
+        event: Input(Input::Controller),
+        test: test_input_controller_connection(ControllerConnectionArgs::new(
+            1,
+            ConnectionState::Removed,
+            "Pad".into(),
+            Some("030000005e0400008e02000014010000".into()),
+        )),
+    }
+}
+
+/// How the inner deadzone of a stick is applied.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub enum Deadzone {
+    /// Each axis is filtered separately.
+    ///
+    /// Makes it easier to move along a single axis,
+    /// but snaps diagonal movement near the center to the axes.
+    Axial(f64),
+    /// The distance from the center is filtered.
+    ///
+    /// Preserves the direction of the stick.
+    Radial(f64),
+}
+
+/// Maps the position of a stick after removing deadzones.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub enum ResponseCurve {
+    /// Position is proportional to the stick movement.
+    Linear,
+    /// Position is raised to a power.
+    ///
+    /// Powers above `1.0` give finer control near the center.
+    Power(f64),
+}
+
+impl ResponseCurve {
+    /// Applies the curve to a value in [0.0, 1.0].
+    pub fn apply(&self, value: f64) -> f64 {
+        match *self {
+            ResponseCurve::Linear => value,
+            ResponseCurve::Power(p) => value.powf(p),
+        }
+    }
+}
+
+/// Settings for a pair of axes that is processed as a stick.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct StickSettings {
+    /// The horizontal and vertical axis.
+    pub axes: [u8; 2],
+    /// Positions within the inner deadzone are set to zero.
+    pub deadzone: Deadzone,
+    /// Positions beyond this distance from the center are set to full.
+    pub outer_deadzone: f64,
+    /// Maps the position between the inner and outer deadzone.
+    pub curve: ResponseCurve,
+    /// Scales the position after the response curve.
+    ///
+    /// The result is limited to [-1.0, 1.0].
+    pub sensitivity: f64,
+}
+
+impl StickSettings {
+    /// Creates new stick settings with defaults.
+    ///
+    /// - deadzone: `Radial(0.15)`
+    /// - `outer_deadzone`: 0.95
+    /// - curve: `Linear`
+    /// - sensitivity: 1.0
+    pub fn new(axes: [u8; 2]) -> StickSettings {
+        StickSettings {
+            axes,
+            deadzone: Deadzone::Radial(0.15),
+            outer_deadzone: 0.95,
+            curve: ResponseCurve::Linear,
+            sensitivity: 1.0,
+        }
+    }
+
+    /// Maps a distance from the center in [0.0, 1.0],
+    /// using the outer deadzone, response curve and sensitivity.
+    fn map(&self, inner: f64, value: f64) -> f64 {
+        if value <= inner {
+            return 0.0;
+        }
+        let outer = self.outer_deadzone.max(inner);
+        let t = if outer <= inner {
+            1.0
+        } else {
+            ((value - inner) / (outer - inner)).min(1.0)
+        };
+        (self.curve.apply(t) * self.sensitivity).clamp(0.0, 1.0)
+    }
+
+    /// Processes a raw stick position.
+    pub fn process(&self, pos: [f64; 2]) -> [f64; 2] {
+        match self.deadzone {
+            Deadzone::Axial(inner) => [
+                self.map(inner, pos[0].abs()).copysign(pos[0]),
+                self.map(inner, pos[1].abs()).copysign(pos[1]),
+            ],
+            Deadzone::Radial(inner) => {
+                let len = (pos[0] * pos[0] + pos[1] * pos[1]).sqrt();
+                if len == 0.0 {
+                    return [0.0, 0.0];
+                }
+                let scale = self.map(inner, len.min(1.0)) / len;
+                [pos[0] * scale, pos[1] * scale]
+            }
+        }
+    }
+}
+
+/// Settings for an axis that is processed as a trigger.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct TriggerSettings {
+    /// The trigger axis.
+    pub axis: u8,
+    /// The position of the axis when the trigger is not pressed.
+    ///
+    /// Some backends report triggers in [-1.0, 1.0], others in [0.0, 1.0].
+    pub rest: f64,
+    /// The fraction of the trigger range where the virtual button is pressed.
+    pub threshold: f64,
+    /// The virtual controller button that is pressed and released.
+    ///
+    /// Pick a button that is not used by the controller.
+    pub button: u8,
+}
+
+impl TriggerSettings {
+    /// Creates new trigger settings with defaults.
+    ///
+    /// - rest: 0.0
+    /// - threshold: 0.5
+    pub fn new(axis: u8, button: u8) -> TriggerSettings {
+        TriggerSettings {
+            axis,
+            rest: 0.0,
+            threshold: 0.5,
+            button,
+        }
+    }
+
+    /// Returns the trigger position normalized to [0.0, 1.0].
+    pub fn normalize(&self, position: f64) -> f64 {
+        if self.rest >= 1.0 {
+            return 0.0;
+        }
+        ((position - self.rest) / (1.0 - self.rest)).clamp(0.0, 1.0)
+    }
+}
+
+/// Settings for processing the axes of a controller.
+#[derive(Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug, Default)]
+pub struct AxisSettings {
+    /// Pairs of axes that are processed as sticks.
+    pub sticks: Vec<StickSettings>,
+    /// Axes that are processed as triggers.
+    pub triggers: Vec<TriggerSettings>,
+}
+
+impl AxisSettings {
+    /// Creates new settings that do not change any axes.
+    pub fn new() -> AxisSettings {
+        AxisSettings::default()
+    }
+}
+
+/// Applies deadzones, response curves and trigger thresholds to controller axes.
+///
+/// Axes of sticks are replaced by processed positions.
+/// When a processed position does not change, e.g. while the stick is inside the deadzone,
+/// no event is emitted.
+/// Trigger axes are passed through, but also press and release a virtual button
+/// when crossing the threshold.
+/// All other events are passed through unchanged.
+#[derive(Clone, Debug, Default)]
+pub struct AxisProcessor {
+    /// The settings used for controllers without specific settings.
+    pub settings: AxisSettings,
+    controller_settings: HashMap<u32, AxisSettings>,
+    raw: HashMap<(u32, u8), f64>,
+    processed: HashMap<(u32, u8), f64>,
+    pressed: HashMap<(u32, u8), bool>,
+}
+
+impl AxisProcessor {
+    /// Creates a new axis processor using the same settings for all controllers.
+    pub fn new(settings: AxisSettings) -> AxisProcessor {
+        AxisProcessor {
+            settings,
+            ..AxisProcessor::default()
+        }
+    }
+
+    /// Sets the settings for a specific controller.
+    pub fn set_controller_settings(&mut self, id: u32, settings: AxisSettings) {
+        self.controller_settings.insert(id, settings);
+    }
+
+    /// Removes the settings for a specific controller.
+    pub fn remove_controller_settings(&mut self, id: u32) -> Option<AxisSettings> {
+        self.controller_settings.remove(&id)
+    }
+
+    /// Gets the settings used for a controller.
+    pub fn get_controller_settings(&self, id: u32) -> &AxisSettings {
+        self.controller_settings.get(&id).unwrap_or(&self.settings)
+    }
+
+    /// Releases the virtual buttons of pressed triggers of a controller.
+    fn release_triggers<E, F>(&self, id: u32, e: &E, f: &mut F)
+    where
+        E: GenericEvent,
+        F: FnMut(&E),
+    {
+        let settings = self.get_controller_settings(id);
+        let mut axes: Vec<u8> = self
+            .pressed
+            .iter()
+            .filter(|&(&(pressed_id, _), &pressed)| pressed_id == id && pressed)
+            .map(|(&(_, axis), _)| axis)
+            .collect();
+        axes.sort_unstable();
+        for axis in axes {
+            if let Some(trigger) = settings.triggers.iter().find(|t| t.axis == axis) {
+                let button = Button::Controller(ControllerButton::new(id, trigger.button));
+                let args = ButtonArgs::new(ButtonState::Release, button, None);
+                if let Some(ev) = ButtonEvent::from_button_args(args, e) {
+                    f(&ev);
+                }
+            }
+        }
+    }
+
+    /// Handles an event, calling the closure with the resulting events.
+    ///
+    /// When a controller is connected or disconnected,
+    /// the virtual buttons of its pressed triggers are released.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(&E),
+    {
+        let args = match e.controller_axis_args() {
+            Some(args) => args,
+            None => {
+                if let Some(args) = e.controller_connection_args() {
+                    self.release_triggers(args.id, e, &mut f);
+                    self.raw.retain(|&(id, _), _| id != args.id);
+                    self.processed.retain(|&(id, _), _| id != args.id);
+                    self.pressed.retain(|&(id, _), _| id != args.id);
+                }
+                f(e);
+                return;
+            }
+        };
+        let settings = self
+            .controller_settings
+            .get(&args.id)
+            .unwrap_or(&self.settings);
+        self.raw.insert((args.id, args.axis), args.position);
+
+        if let Some(stick) = settings.sticks.iter().find(|s| s.axes.contains(&args.axis)) {
+            let raw = |axis| *self.raw.get(&(args.id, axis)).unwrap_or(&0.0);
+            let pos = stick.process([raw(stick.axes[0]), raw(stick.axes[1])]);
+            for (&axis, &position) in stick.axes.iter().zip(pos.iter()) {
+                let old = self.processed.insert((args.id, axis), position);
+                if old != Some(position) && (old.is_some() || position != 0.0) {
+                    let new_args = ControllerAxisArgs::new(args.id, axis, position);
+                    if let Some(ev) = ControllerAxisEvent::from_controller_axis_args(new_args, e) {
+                        f(&ev);
+                    }
+                }
+            }
+            return;
+        }
+
+        f(e);
+        if let Some(trigger) = settings.triggers.iter().find(|t| t.axis == args.axis) {
+            let pressed = trigger.normalize(args.position) >= trigger.threshold;
+            let old = self.pressed.insert((args.id, args.axis), pressed);
+            if old.unwrap_or(false) != pressed {
+                let state = if pressed {
+                    ButtonState::Press
+                } else {
+                    ButtonState::Release
+                };
+                let button = Button::Controller(ControllerButton::new(args.id, trigger.button));
+                if let Some(ev) =
+                    ButtonEvent::from_button_args(ButtonArgs::new(state, button, None), e)
+                {
+                    f(&ev);
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod controller_axis_tests {
     use super::*;
@@ -122,3 +471,83 @@ mod controller_axis_tests {
         assert_eq!(a, b);
     }
 }
+
+#[cfg(test)]
+mod axis_processor_tests {
+    use super::*;
+
+    #[test]
+    fn test_stick_deadzone() {
+        let mut stick = StickSettings::new([0, 1]);
+        stick.deadzone = Deadzone::Radial(0.25);
+        stick.outer_deadzone = 0.75;
+        assert_eq!(stick.process([0.125, 0.125]), [0.0, 0.0]);
+        assert_eq!(stick.process([0.0, 0.5]), [0.0, 0.5]);
+        assert_eq!(stick.process([-0.8, 0.0]), [-1.0, 0.0]);
+        stick.deadzone = Deadzone::Axial(0.25);
+        assert_eq!(stick.process([0.125, 0.5]), [0.0, 0.5]);
+        stick.curve = ResponseCurve::Power(2.0);
+        assert_eq!(stick.process([0.125, -0.5]), [0.0, -0.25]);
+    }
+
+    #[test]
+    fn test_axis_processor() {
+        let mut stick = StickSettings::new([0, 1]);
+        stick.deadzone = Deadzone::Axial(0.25);
+        stick.outer_deadzone = 0.75;
+        let mut settings = AxisSettings::new();
+        settings.sticks.push(stick);
+        settings.triggers.push(TriggerSettings::new(2, 20));
+        let mut processor = AxisProcessor::new(AxisSettings::new());
+        processor.set_controller_settings(0, settings);
+
+        let events: Vec<Event> = vec![
+            ControllerAxisArgs::new(0, 0, 0.1).into(),
+            ControllerAxisArgs::new(0, 1, 0.5).into(),
+            ControllerAxisArgs::new(0, 2, 0.7).into(),
+            ControllerAxisArgs::new(0, 2, 0.2).into(),
+            ControllerAxisArgs::new(1, 0, 0.1).into(),
+        ];
+        let mut out = vec![];
+        for e in &events {
+            processor.event(e, |e: &Event| out.push(e.clone()));
+        }
+        let button = |state| -> Event {
+            ButtonArgs::new(state, ControllerButton::new(0, 20).into(), None).into()
+        };
+        let expected: Vec<Event> = vec![
+            ControllerAxisArgs::new(0, 1, 0.5).into(),
+            ControllerAxisArgs::new(0, 2, 0.7).into(),
+            button(ButtonState::Press),
+            ControllerAxisArgs::new(0, 2, 0.2).into(),
+            button(ButtonState::Release),
+            ControllerAxisArgs::new(1, 0, 0.1).into(),
+        ];
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_axis_processor_disconnect() {
+        let mut settings = AxisSettings::new();
+        settings.triggers.push(TriggerSettings::new(2, 20));
+        let mut processor = AxisProcessor::new(settings);
+
+        let args = ControllerConnectionArgs::new(0, ConnectionState::Removed, "Pad".into(), None);
+        let removed: Event = Input::from(args).into();
+        let events: Vec<Event> = vec![ControllerAxisArgs::new(0, 2, 0.9).into(), removed.clone()];
+        let mut out = vec![];
+        for e in &events {
+            processor.event(e, |e: &Event| out.push(e.clone()));
+        }
+        let button = |state| -> Event {
+            ButtonArgs::new(state, ControllerButton::new(0, 20).into(), None).into()
+        };
+        let expected: Vec<Event> = vec![
+            ControllerAxisArgs::new(0, 2, 0.9).into(),
+            button(ButtonState::Press),
+            button(ButtonState::Release),
+            removed,
+        ];
+        assert_eq!(out, expected);
+    }
+}
@@ -0,0 +1,489 @@
+//! Typed custom events and serialization of events.
+//!
+//! Implement [`CustomArgs`] to associate a type with an event id,
+//! then use [`CustomEvent`] to create and read custom events without manual downcasting:
+//!
+//! ```ignore
+//! struct Score(u32);
+//!
+//! impl CustomArgs for Score {
+//!     fn event_id() -> EventId {
+//!         EventId("game/score")
+//!     }
+//! }
+//!
+//! let e = Event::from_custom_args(Score(10), &old_event).unwrap();
+//! if let Some(score) = e.custom::<Score>()? {
+//!     ...
+//! }
+//! ```
+//!
+//! Custom events store their arguments as `Arc<dyn Any + Send + Sync>`,
+//! which can not be serialized without knowing the type.
+//! Applications register serializer and deserializer functions per event id in a [`Registry`],
+//! which is passed explicitly when serializing and deserializing `Event`.
+//! Serializing or deserializing an unregistered custom event returns an error,
+//! as does serializing or deserializing a custom event without a registry.
+//!
+//! ```ignore
+//! const SCORE: EventId = EventId("game/score");
+//!
+//! let mut registry = Registry::new();
+//! registry.register::<u32>(SCORE);
+//! let e = Event::Custom(SCORE, Arc::new(10_u32), None);
+//! let json = serde_json::to_string(&registry.event(&e))?;
+//! let e = registry.seed().deserialize(&mut serde_json::Deserializer::from_str(&json))?;
+//! ```
+
+use std::{any::Any, collections::BTreeMap, error::Error, fmt, sync::Arc};
+
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{codec, event_id::EventId, Event, Input, Loop, TimeStamp};
+
+/// Associates a type with the event id of custom events using it as arguments.
+pub trait CustomArgs: Any + Send + Sync {
+    /// The event id of custom events with these arguments.
+    fn event_id() -> EventId;
+}
+
+/// The arguments of a custom event do not have the type associated with its event id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CustomTypeError {
+    /// The event id of the custom event.
+    pub id: EventId,
+    /// The name of the expected type.
+    pub expected: &'static str,
+}
+
+impl fmt::Display for CustomTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Custom event `{}` does not have arguments of type `{}`",
+            self.id.0, self.expected
+        )
+    }
+}
+
+impl Error for CustomTypeError {}
+
+/// A custom event with typed arguments.
+pub trait CustomEvent: Sized {
+    /// Creates a custom event with the event id of the arguments.
+    ///
+    /// Preserves time stamp from original input or custom event, if any.
+    fn from_custom_args<T: CustomArgs>(args: T, old_event: &Self) -> Option<Self>;
+    /// Returns the arguments if this is a custom event with the event id of `T`.
+    ///
+    /// Returns an error if the event id matches, but the arguments have a different type.
+    fn custom<T: CustomArgs>(&self) -> Result<Option<Arc<T>>, CustomTypeError>;
+}
+
+impl CustomEvent for Event {
+    fn from_custom_args<T: CustomArgs>(args: T, old_event: &Self) -> Option<Self> {
+        let timestamp = match *old_event {
+            Event::Input(_, x) | Event::Custom(_, _, x) => x,
+            Event::Loop(_) => None,
+        };
+        Some(Event::Custom(T::event_id(), Arc::new(args), timestamp))
+    }
+
+    fn custom<T: CustomArgs>(&self) -> Result<Option<Arc<T>>, CustomTypeError> {
+        match *self {
+            Event::Custom(id, ref args, _) if id == T::event_id() => {
+                match args.clone().downcast::<T>() {
+                    Ok(args) => Ok(Some(args)),
+                    Err(_) => Err(CustomTypeError {
+                        id,
+                        expected: std::any::type_name::<T>(),
+                    }),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Serializes the arguments of a custom event to bytes.
+pub type SerializeFn = fn(&(dyn Any + Send + Sync)) -> Result<Vec<u8>, String>;
+/// Deserializes the arguments of a custom event from bytes.
+pub type DeserializeFn = fn(&[u8]) -> Result<Arc<dyn Any + Send + Sync>, String>;
+
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+    id: EventId,
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+}
+
+/// Stores serializer and deserializer functions of custom events.
+///
+/// A registry is passed explicitly to the serializer and decoder,
+/// so different recordings or libraries can use different custom events.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    entries: BTreeMap<&'static str, Entry>,
+}
+
+impl Registry {
+    /// Creates an empty registry.
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Registers serializer and deserializer functions for a custom event.
+    ///
+    /// Replaces the functions of a previous registration with the same id.
+    pub fn register_with(
+        &mut self,
+        id: EventId,
+        serialize: SerializeFn,
+        deserialize: DeserializeFn,
+    ) {
+        self.entries.insert(
+            id.0,
+            Entry {
+                id,
+                serialize,
+                deserialize,
+            },
+        );
+    }
+
+    /// Registers a custom event with arguments of type `T`,
+    /// using the binary encoding in the [`codec`] module.
+    ///
+    /// Replaces the functions of a previous registration with the same id.
+    pub fn register<T>(&mut self, id: EventId)
+    where
+        T: Serialize + DeserializeOwned + Any + Send + Sync,
+    {
+        self.register_with(id, serialize_value::<T>, deserialize_value::<T>)
+    }
+
+    /// Registers the custom event of typed arguments,
+    /// using the binary encoding in the [`codec`] module.
+    pub fn register_args<T>(&mut self)
+    where
+        T: CustomArgs + Serialize + DeserializeOwned,
+    {
+        self.register::<T>(T::event_id())
+    }
+
+    /// Removes the registration of a custom event.
+    ///
+    /// Returns `true` if the custom event was registered.
+    pub fn unregister(&mut self, id: EventId) -> bool {
+        self.entries.remove(id.0).is_some()
+    }
+
+    /// Returns `true` if a custom event is registered.
+    pub fn is_registered(&self, id: EventId) -> bool {
+        self.entries.contains_key(id.0)
+    }
+
+    /// Returns a wrapper serializing an event, including registered custom events.
+    pub fn event<'a>(&'a self, e: &'a Event) -> RegisteredEvent<'a> {
+        RegisteredEvent { registry: self, e }
+    }
+
+    /// Returns a seed deserializing an event, including registered custom events.
+    pub fn seed(&self) -> EventSeed<'_> {
+        EventSeed { registry: self }
+    }
+
+    /// Serializes the arguments of a custom event.
+    pub(crate) fn serialize_custom(
+        &self,
+        id: EventId,
+        args: &(dyn Any + Send + Sync),
+    ) -> Result<Vec<u8>, String> {
+        match self.entries.get(id.0) {
+            Some(entry) => (entry.serialize)(args)
+                .map_err(|err| format!("Could not serialize custom event `{}`: {}", id.0, err)),
+            None => Err(format!("Custom event `{}` is not registered", id.0)),
+        }
+    }
+
+    /// Deserializes the arguments of a custom event.
+    pub(crate) fn deserialize_custom(
+        &self,
+        name: &str,
+        data: &[u8],
+    ) -> Result<(EventId, Arc<dyn Any + Send + Sync>), String> {
+        match self.entries.get(name) {
+            Some(entry) => match (entry.deserialize)(data) {
+                Ok(args) => Ok((entry.id, args)),
+                Err(err) => Err(format!(
+                    "Could not deserialize custom event `{}`: {}",
+                    name, err
+                )),
+            },
+            None => Err(format!("Custom event `{}` is not registered", name)),
+        }
+    }
+}
+
+fn serialize_value<T>(args: &(dyn Any + Send + Sync)) -> Result<Vec<u8>, String>
+where
+    T: Serialize + Any,
+{
+    let args = match args.downcast_ref::<T>() {
+        Some(x) => x,
+        None => {
+            return Err(format!(
+                "Expected arguments of type `{}`",
+                std::any::type_name::<T>()
+            ))
+        }
+    };
+    let mut data = vec![];
+    codec::encode_value(&mut data, args).map_err(|err| err.to_string())?;
+    Ok(data)
+}
+
+fn deserialize_value<T>(data: &[u8]) -> Result<Arc<dyn Any + Send + Sync>, String>
+where
+    T: DeserializeOwned + Any + Send + Sync,
+{
+    let args: T = codec::decode_value(data).map_err(|err| err.to_string())?;
+    Ok(Arc::new(args))
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Event")]
+enum EventRef<'a> {
+    Input(&'a Input, Option<TimeStamp>),
+    Loop(&'a Loop),
+    Custom(&'a str, Vec<u8>, Option<TimeStamp>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Event")]
+enum EventOwned {
+    Input(Input, Option<TimeStamp>),
+    Loop(Loop),
+    Custom(String, Vec<u8>, Option<TimeStamp>),
+}
+
+fn serialize_event<S: Serializer>(
+    e: &Event,
+    registry: Option<&Registry>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let e = match *e {
+        Event::Input(ref input, timestamp) => EventRef::Input(input, timestamp),
+        Event::Loop(ref args) => EventRef::Loop(args),
+        Event::Custom(id, ref args, timestamp) => EventRef::Custom(
+            id.0,
+            match registry {
+                Some(registry) => registry.serialize_custom(id, &**args),
+                None => Err(no_registry(id.0)),
+            }
+            .map_err(serde::ser::Error::custom)?,
+            timestamp,
+        ),
+    };
+    e.serialize(serializer)
+}
+
+fn deserialize_event<'de, D: Deserializer<'de>>(
+    registry: Option<&Registry>,
+    deserializer: D,
+) -> Result<Event, D::Error> {
+    Ok(match EventOwned::deserialize(deserializer)? {
+        EventOwned::Input(input, timestamp) => Event::Input(input, timestamp),
+        EventOwned::Loop(args) => Event::Loop(args),
+        EventOwned::Custom(name, data, timestamp) => {
+            let (id, args) = match registry {
+                Some(registry) => registry.deserialize_custom(&name, &data),
+                None => Err(no_registry(&name)),
+            }
+            .map_err(serde::de::Error::custom)?;
+            Event::Custom(id, args, timestamp)
+        }
+    })
+}
+
+fn no_registry(name: &str) -> String {
+    format!(
+        "Custom event `{}` requires a registry, see `Registry::event` and `Registry::seed`",
+        name
+    )
+}
+
+/// Serializing custom events without a registry returns an error,
+/// see [`Registry::event`].
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_event(self, None, serializer)
+    }
+}
+
+/// Deserializing custom events without a registry returns an error,
+/// see [`Registry::seed`].
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_event(None, deserializer)
+    }
+}
+
+/// Serializes an event using the functions of a registry for custom events.
+///
+/// Created by [`Registry::event`].
+#[derive(Copy, Clone, Debug)]
+pub struct RegisteredEvent<'a> {
+    registry: &'a Registry,
+    e: &'a Event,
+}
+
+impl Serialize for RegisteredEvent<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_event(self.e, Some(self.registry), serializer)
+    }
+}
+
+/// Deserializes an event using the functions of a registry for custom events.
+///
+/// Created by [`Registry::seed`].
+#[derive(Copy, Clone, Debug)]
+pub struct EventSeed<'a> {
+    registry: &'a Registry,
+}
+
+impl<'de> DeserializeSeed<'de> for EventSeed<'_> {
+    type Value = Event;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Event, D::Error> {
+        deserialize_event(Some(self.registry), deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GenericEvent;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Score {
+        player: String,
+        points: u32,
+    }
+
+    const SCORE: EventId = EventId("test/custom_score");
+    const UNREGISTERED: EventId = EventId("test/custom_unregistered");
+
+    #[test]
+    fn test_custom_round_trip() {
+        let mut registry = Registry::new();
+        registry.register::<Score>(SCORE);
+        assert!(registry.is_registered(SCORE));
+        let score = Score {
+            player: "a".into(),
+            points: 10,
+        };
+        let e = Event::Custom(SCORE, Arc::new(score), Some(42));
+        let json = serde_json::to_string(&registry.event(&e)).unwrap();
+        let decoded = registry
+            .seed()
+            .deserialize(&mut serde_json::Deserializer::from_str(&json))
+            .unwrap();
+        assert_eq!(decoded.event_id(), SCORE);
+        assert_eq!(decoded.time_stamp(), Some(42));
+        if let Event::Custom(_, args, _) = decoded {
+            assert_eq!(
+                args.downcast_ref::<Score>(),
+                Some(&Score {
+                    player: "a".into(),
+                    points: 10
+                })
+            );
+        } else {
+            panic!("Expected custom event");
+        }
+
+        let mut encoder = codec::Encoder::with_registry(vec![], registry.clone()).unwrap();
+        encoder.encode(&e).unwrap();
+        let data = encoder.into_inner();
+        let decoded = codec::Decoder::with_registry(&data[..], registry.clone())
+            .unwrap()
+            .decode()
+            .unwrap();
+        assert_eq!(decoded.map(|e| e.event_id()), Some(SCORE));
+
+        let wrong = Event::Custom(SCORE, Arc::new(10_u32), None);
+        assert!(serde_json::to_string(&registry.event(&wrong)).is_err());
+
+        // Each registry has its own custom events.
+        let err = serde_json::to_string(&Registry::new().event(&e)).unwrap_err();
+        assert!(err.to_string().contains("is not registered"));
+        let err = serde_json::to_string(&e).unwrap_err();
+        assert!(err.to_string().contains("requires a registry"));
+        let err = serde_json::from_str::<Event>(&json).unwrap_err();
+        assert!(err.to_string().contains("requires a registry"));
+        assert!(registry.unregister(SCORE));
+        assert!(!registry.is_registered(SCORE));
+    }
+
+    #[test]
+    fn test_custom_unregistered() {
+        let registry = Registry::new();
+        let e = Event::Custom(UNREGISTERED, Arc::new(()), None);
+        let err = serde_json::to_string(&registry.event(&e)).unwrap_err();
+        assert!(err.to_string().contains("is not registered"));
+        let err = registry
+            .seed()
+            .deserialize(&mut serde_json::Deserializer::from_str(
+                r#"{"Custom":["test/custom_unregistered",[],null]}"#,
+            ))
+            .unwrap_err();
+        assert!(err.to_string().contains("is not registered"));
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Level(u32);
+
+    impl CustomArgs for Level {
+        fn event_id() -> EventId {
+            EventId("test/custom_level")
+        }
+    }
+
+    #[derive(Debug)]
+    struct WrongLevel;
+
+    impl CustomArgs for WrongLevel {
+        fn event_id() -> EventId {
+            EventId("test/custom_level")
+        }
+    }
+
+    #[test]
+    fn test_custom_args() {
+        let old: Event = (Input::Focus(true), Some(7)).into();
+        let e = Event::from_custom_args(Level(3), &old).unwrap();
+        assert_eq!(e.event_id(), Level::event_id());
+        assert_eq!(e.time_stamp(), Some(7));
+        assert_eq!(e.custom::<Level>().unwrap().as_deref(), Some(&Level(3)));
+        assert_eq!(old.custom::<Level>(), Ok(None));
+        let err = e.custom::<WrongLevel>().unwrap_err();
+        assert_eq!(err.id, Level::event_id());
+        assert!(err.to_string().contains("WrongLevel"));
+
+        let mut registry = Registry::new();
+        registry.register_args::<Level>();
+        let json = serde_json::to_string(&registry.event(&e)).unwrap();
+        let decoded = registry
+            .seed()
+            .deserialize(&mut serde_json::Deserializer::from_str(&json))
+            .unwrap();
+        assert_eq!(
+            decoded.custom::<Level>().unwrap().as_deref(),
+            Some(&Level(3))
+        );
+    }
+}
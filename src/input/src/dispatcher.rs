@@ -0,0 +1,206 @@
+//! Routes events to handlers by event id.
+//!
+//! Components subscribe handlers to the events they are interested in,
+//! instead of checking every kind of event in one function.
+//! Handlers are called with a mutable context, e.g. the application model,
+//! in order of priority, highest first.
+//! A handler can consume an event, so handlers with lower priority do not see it.
+//!
+//! ```ignore
+//! let mut dispatcher: Dispatcher<Game> = Dispatcher::new();
+//! dispatcher.subscribe(event_id::UPDATE, 0, |game, e| {
+//!     game.update(e.update_args().unwrap().dt);
+//!     Propagation::Pass
+//! });
+//! while let Some(e) = events.next(&mut window) {
+//!     dispatcher.dispatch(&mut game, &e);
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::{event_id::EventId, Event, GenericEvent};
+
+/// Tells whether an event should be passed on to handlers with lower priority.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Propagation {
+    /// Pass the event on to the next handler.
+    Pass,
+    /// Consume the event.
+    Consume,
+}
+
+/// Identifies a subscribed handler.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandlerId(u64);
+
+type Handler<C, E> = Box<dyn FnMut(&mut C, &E) -> Propagation>;
+
+struct Subscription<C, E> {
+    id: HandlerId,
+    event_id: Option<EventId>,
+    priority: i32,
+    handler: Handler<C, E>,
+}
+
+/// Routes events to handlers subscribed to event ids.
+pub struct Dispatcher<C, E = Event> {
+    subscriptions: Vec<Subscription<C, E>>,
+    next_id: u64,
+}
+
+impl<C, E> fmt::Debug for Dispatcher<C, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dispatcher")
+            .field("handlers", &self.subscriptions.len())
+            .finish()
+    }
+}
+
+impl<C, E> Default for Dispatcher<C, E>
+where
+    E: GenericEvent,
+{
+    fn default() -> Self {
+        Dispatcher::new()
+    }
+}
+
+impl<C, E> Dispatcher<C, E>
+where
+    E: GenericEvent,
+{
+    /// Creates a new dispatcher without handlers.
+    pub fn new() -> Self {
+        Dispatcher {
+            subscriptions: vec![],
+            next_id: 0,
+        }
+    }
+
+    fn insert(
+        &mut self,
+        event_id: Option<EventId>,
+        priority: i32,
+        handler: Handler<C, E>,
+    ) -> HandlerId {
+        let id = HandlerId(self.next_id);
+        self.next_id += 1;
+        // Handlers with equal priority are called in order of subscription.
+        let index = self
+            .subscriptions
+            .iter()
+            .position(|s| s.priority < priority)
+            .unwrap_or(self.subscriptions.len());
+        self.subscriptions.insert(
+            index,
+            Subscription {
+                id,
+                event_id,
+                priority,
+                handler,
+            },
+        );
+        id
+    }
+
+    /// Subscribes a handler to events with an event id.
+    ///
+    /// Handlers with higher priority are called first.
+    pub fn subscribe<F>(&mut self, event_id: EventId, priority: i32, handler: F) -> HandlerId
+    where
+        F: 'static + FnMut(&mut C, &E) -> Propagation,
+    {
+        self.insert(Some(event_id), priority, Box::new(handler))
+    }
+
+    /// Subscribes a handler to all events.
+    ///
+    /// Handlers with higher priority are called first.
+    pub fn subscribe_all<F>(&mut self, priority: i32, handler: F) -> HandlerId
+    where
+        F: 'static + FnMut(&mut C, &E) -> Propagation,
+    {
+        self.insert(None, priority, Box::new(handler))
+    }
+
+    /// Removes a handler.
+    ///
+    /// Returns `true` if the handler was subscribed.
+    pub fn unsubscribe(&mut self, id: HandlerId) -> bool {
+        let len = self.subscriptions.len();
+        self.subscriptions.retain(|s| s.id != id);
+        self.subscriptions.len() != len
+    }
+
+    /// Returns the number of subscribed handlers.
+    pub fn len(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    /// Returns `true` if there are no subscribed handlers.
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+
+    /// Calls the handlers subscribed to the event, in order of priority.
+    ///
+    /// Returns `true` if a handler consumed the event.
+    pub fn dispatch(&mut self, context: &mut C, e: &E) -> bool {
+        let event_id = e.event_id();
+        for s in &mut self.subscriptions {
+            if s.event_id.map(|id| id == event_id).unwrap_or(true)
+                && (s.handler)(context, e) == Propagation::Consume
+            {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{event_id, UpdateArgs, UpdateEvent};
+
+    #[test]
+    fn test_dispatcher() {
+        let mut dispatcher: Dispatcher<Vec<&'static str>> = Dispatcher::new();
+        dispatcher.subscribe(event_id::UPDATE, 0, |log, e: &Event| {
+            assert!(e.update_args().is_some());
+            log.push("update");
+            Propagation::Pass
+        });
+        dispatcher.subscribe_all(-1, |log, _| {
+            log.push("all");
+            Propagation::Pass
+        });
+        let menu = dispatcher.subscribe(event_id::UPDATE, 10, |log, _| {
+            log.push("menu");
+            Propagation::Consume
+        });
+        let custom = EventId("test/dispatch");
+        dispatcher.subscribe(custom, 0, |log, _| {
+            log.push("custom");
+            Propagation::Pass
+        });
+
+        let update: Event = UpdateArgs { dt: 0.1 }.into();
+        let mut log = vec![];
+        assert!(dispatcher.dispatch(&mut log, &update));
+        assert_eq!(log, vec!["menu"]);
+
+        assert!(dispatcher.unsubscribe(menu));
+        assert!(!dispatcher.unsubscribe(menu));
+        log.clear();
+        assert!(!dispatcher.dispatch(&mut log, &update));
+        assert_eq!(log, vec!["update", "all"]);
+
+        log.clear();
+        dispatcher.dispatch(&mut log, &Event::Custom(custom, Arc::new(()), None));
+        assert_eq!(log, vec!["custom", "all"]);
+    }
+}
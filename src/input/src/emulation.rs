@@ -0,0 +1,607 @@
+//! Emulation of mouse input with touches, and touches with the mouse.
+//!
+//! Applications written for the mouse ignore touch events,
+//! and applications written for touch screens ignore the mouse.
+//! The adapters in this module add emulated events after the original events,
+//! such that both kinds of input can be handled.
+//!
+//! When using both adapters, pass events through [`MouseToTouch`] first,
+//! followed by [`TouchToMouse`].
+//! Touches emulated from the mouse use the device id [`MOUSE_TOUCH_DEVICE`],
+//! which are ignored by [`TouchToMouse`], so events are not converted back.
+//!
+//! ```ignore
+//! let mut mouse_to_touch = MouseToTouch::new();
+//! let mut touch_to_mouse = TouchToMouse::new(TouchToMouseSettings::new());
+//! while let Some(e) = events.next(&mut window) {
+//!     mouse_to_touch.event(&e, |e| touch_to_mouse.event(e, |e| app.event(e)));
+//! }
+//! ```
+
+use crate::{
+    Button, ButtonArgs, ButtonEvent, ButtonState, GenericEvent, MouseButton, MouseCursorEvent,
+    TimeStamp, Touch, TouchArgs, TouchEvent,
+};
+
+/// The device id of touches emulated with the mouse.
+pub const MOUSE_TOUCH_DEVICE: i64 = -1;
+
+fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Returns the size of the window in points from resize and render events.
+fn window_size<E: GenericEvent>(e: &E) -> Option<[f64; 2]> {
+    e.resize_args()
+        .map(|args| args.window_size)
+        .or_else(|| e.render_args().map(|args| args.window_size))
+}
+
+/// Thresholds used to emulate the mouse with touches.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct TouchToMouseSettings {
+    /// The distance a touch must move before it is a drag, normalized 0..1.
+    pub drag_distance: f64,
+    /// The duration in milliseconds before a touch is a right click.
+    pub long_press_time: TimeStamp,
+}
+
+impl TouchToMouseSettings {
+    /// Creates new touch to mouse settings with defaults.
+    ///
+    /// - `drag_distance`: 0.02
+    /// - `long_press_time`: 500
+    pub fn new() -> TouchToMouseSettings {
+        TouchToMouseSettings {
+            drag_distance: 0.02,
+            long_press_time: 500,
+        }
+    }
+}
+
+impl Default for TouchToMouseSettings {
+    fn default() -> TouchToMouseSettings {
+        TouchToMouseSettings::new()
+    }
+}
+
+/// The state of the primary touch.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Primary {
+    /// Waiting to know whether the touch is a click, drag or long press.
+    Pending {
+        device: i64,
+        id: i64,
+        start: [f64; 2],
+        start_time: f64,
+    },
+    /// The left mouse button is held down.
+    Dragging { device: i64, id: i64 },
+    /// A right click was emitted, the touch only moves the cursor.
+    LongPressed { device: i64, id: i64 },
+}
+
+impl Primary {
+    fn is(&self, args: &TouchArgs) -> bool {
+        let (device, id) = match *self {
+            Primary::Pending { device, id, .. }
+            | Primary::Dragging { device, id }
+            | Primary::LongPressed { device, id } => (device, id),
+        };
+        device == args.device && id == args.id
+    }
+}
+
+/// Emulates the mouse with the first finger touching.
+///
+/// The touch moves the mouse cursor.
+/// A short touch is a left click, and moving the touch drags with the left mouse button.
+/// Holding the touch still is a right click.
+/// Other fingers are ignored while the first finger touches.
+///
+/// Time is measured from the time stamps of input events.
+/// Between input events, time is advanced by update events,
+/// which recognizes long presses while the touch is still held.
+/// Without update events, a long press is recognized when the touch moves or ends.
+///
+/// Touch positions are converted to window points using the size from resize or render events.
+#[derive(Copy, Clone, Debug)]
+pub struct TouchToMouse {
+    /// Whether to emulate the mouse.
+    pub enabled: bool,
+    /// The thresholds used to emulate the mouse.
+    pub settings: TouchToMouseSettings,
+    window_size: [f64; 2],
+    primary: Option<Primary>,
+    /// Current time in milliseconds.
+    time: f64,
+}
+
+impl TouchToMouse {
+    /// Creates a new enabled adapter.
+    pub fn new(settings: TouchToMouseSettings) -> TouchToMouse {
+        TouchToMouse {
+            enabled: true,
+            settings,
+            window_size: [0.0; 2],
+            primary: None,
+            time: 0.0,
+        }
+    }
+
+    /// Sets the size of the window in points.
+    pub fn set_window_size(&mut self, window_size: [f64; 2]) {
+        self.window_size = window_size;
+    }
+
+    /// Returns `true` if the left mouse button is emulated as held down.
+    pub fn is_dragging(&self) -> bool {
+        matches!(self.primary, Some(Primary::Dragging { .. }))
+    }
+
+    fn is_long_press(&self, start_time: f64) -> bool {
+        self.time - start_time >= self.settings.long_press_time as f64
+    }
+
+    fn window_position(&self, position: [f64; 2]) -> [f64; 2] {
+        [
+            position[0] * self.window_size[0],
+            position[1] * self.window_size[1],
+        ]
+    }
+
+    /// Handles an event, calling the closure with the event and emulated mouse events.
+    ///
+    /// Emulated events are emitted after the touch event,
+    /// except right clicks, which are emitted before the update event that completed them.
+    /// When disabled, events are passed through, but a held down left mouse button is released.
+    /// Losing focus also releases the left mouse button and forgets the touch.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(&E),
+    {
+        if let Some(t) = e.time_stamp() {
+            self.time = self.time.max(t as f64);
+        }
+        if let Some(size) = window_size(e) {
+            self.window_size = size;
+        }
+        if !self.enabled {
+            if let Some(Primary::Dragging { .. }) = self.primary.take() {
+                emit_button(e, ButtonState::Release, MouseButton::Left, &mut f);
+            }
+            f(e);
+            return;
+        }
+        if let Some(args) = e.update_args() {
+            self.time += args.dt * 1000.0;
+            if let Some(Primary::Pending {
+                device,
+                id,
+                start_time,
+                ..
+            }) = self.primary
+            {
+                if self.is_long_press(start_time) {
+                    self.primary = Some(Primary::LongPressed { device, id });
+                    emit_button(e, ButtonState::Press, MouseButton::Right, &mut f);
+                    emit_button(e, ButtonState::Release, MouseButton::Right, &mut f);
+                }
+            }
+            f(e);
+            return;
+        }
+
+        f(e);
+        if e.focus_args() == Some(false) {
+            if let Some(Primary::Dragging { .. }) = self.primary.take() {
+                emit_button(e, ButtonState::Release, MouseButton::Left, &mut f);
+            }
+            return;
+        }
+        let args = match e.touch_args() {
+            Some(args) if args.device != MOUSE_TOUCH_DEVICE => args,
+            _ => return,
+        };
+        let pos = self.window_position(args.position());
+        // A start for the touch of the primary means its end was lost.
+        if let (Touch::Start, Some(primary)) = (args.touch, self.primary) {
+            if primary.is(&args) {
+                self.primary = None;
+                if let Primary::Dragging { .. } = primary {
+                    emit_button(e, ButtonState::Release, MouseButton::Left, &mut f);
+                }
+            }
+        }
+        match (args.touch, self.primary) {
+            (Touch::Start, None) => {
+                self.primary = Some(Primary::Pending {
+                    device: args.device,
+                    id: args.id,
+                    start: args.position(),
+                    start_time: self.time,
+                });
+                emit_cursor(e, pos, &mut f);
+            }
+            (Touch::Move, Some(primary)) if primary.is(&args) => {
+                if let Primary::Pending {
+                    device,
+                    id,
+                    start,
+                    start_time,
+                } = primary
+                {
+                    if distance(start, args.position()) <= self.settings.drag_distance {
+                        if self.is_long_press(start_time) {
+                            self.primary = Some(Primary::LongPressed { device, id });
+                            emit_button(e, ButtonState::Press, MouseButton::Right, &mut f);
+                            emit_button(e, ButtonState::Release, MouseButton::Right, &mut f);
+                        }
+                        return;
+                    }
+                    self.primary = Some(Primary::Dragging { device, id });
+                    emit_button(e, ButtonState::Press, MouseButton::Left, &mut f);
+                }
+                emit_cursor(e, pos, &mut f);
+            }
+            (Touch::End, Some(primary)) if primary.is(&args) => {
+                self.primary = None;
+                emit_cursor(e, pos, &mut f);
+                match primary {
+                    Primary::Pending { start_time, .. } if self.is_long_press(start_time) => {
+                        emit_button(e, ButtonState::Press, MouseButton::Right, &mut f);
+                        emit_button(e, ButtonState::Release, MouseButton::Right, &mut f);
+                    }
+                    Primary::Pending { .. } => {
+                        emit_button(e, ButtonState::Press, MouseButton::Left, &mut f);
+                        emit_button(e, ButtonState::Release, MouseButton::Left, &mut f);
+                    }
+                    Primary::Dragging { .. } => {
+                        emit_button(e, ButtonState::Release, MouseButton::Left, &mut f);
+                    }
+                    Primary::LongPressed { .. } => {}
+                }
+            }
+            (Touch::Cancel, Some(primary)) if primary.is(&args) => {
+                self.primary = None;
+                if let Primary::Dragging { .. } = primary {
+                    emit_button(e, ButtonState::Release, MouseButton::Left, &mut f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn emit_cursor<E, F>(e: &E, pos: [f64; 2], f: &mut F)
+where
+    E: GenericEvent,
+    F: FnMut(&E),
+{
+    if let Some(ev) = MouseCursorEvent::from_pos(pos, e) {
+        f(&ev);
+    }
+}
+
+fn emit_button<E, F>(e: &E, state: ButtonState, button: MouseButton, f: &mut F)
+where
+    E: GenericEvent,
+    F: FnMut(&E),
+{
+    let args = ButtonArgs::new(state, Button::Mouse(button), None);
+    if let Some(ev) = ButtonEvent::from_button_args(args, e) {
+        f(&ev);
+    }
+}
+
+/// Emulates a touch with mouse drags.
+///
+/// Pressing the mouse button starts a touch at the mouse cursor,
+/// moving the mouse cursor moves the touch, and releasing the button ends the touch.
+/// Touches have device id [`MOUSE_TOUCH_DEVICE`] and touch id 0.
+///
+/// Positions are converted to normalized touch positions using the size from resize or render events.
+#[derive(Copy, Clone, Debug)]
+pub struct MouseToTouch {
+    /// Whether to emulate touches.
+    pub enabled: bool,
+    /// The mouse button that touches.
+    pub button: MouseButton,
+    window_size: [f64; 2],
+    cursor: [f64; 2],
+    touching: bool,
+}
+
+impl Default for MouseToTouch {
+    fn default() -> MouseToTouch {
+        MouseToTouch::new()
+    }
+}
+
+impl MouseToTouch {
+    /// Creates a new enabled adapter, touching with the left mouse button.
+    pub fn new() -> MouseToTouch {
+        MouseToTouch {
+            enabled: true,
+            button: MouseButton::Left,
+            window_size: [0.0; 2],
+            cursor: [0.0; 2],
+            touching: false,
+        }
+    }
+
+    /// Sets the size of the window in points.
+    pub fn set_window_size(&mut self, window_size: [f64; 2]) {
+        self.window_size = window_size;
+    }
+
+    /// Returns `true` if a touch is emulated.
+    pub fn is_touching(&self) -> bool {
+        self.touching
+    }
+
+    fn touch_position(&self, pos: [f64; 2]) -> [f64; 2] {
+        let normalize = |x: f64, size: f64| if size > 0.0 { x / size } else { 0.0 };
+        [
+            normalize(pos[0], self.window_size[0]),
+            normalize(pos[1], self.window_size[1]),
+        ]
+    }
+
+    fn emit<E, F>(&self, e: &E, touch: Touch, f: &mut F)
+    where
+        E: GenericEvent,
+        F: FnMut(&E),
+    {
+        let args = TouchArgs::new(
+            MOUSE_TOUCH_DEVICE,
+            0,
+            self.touch_position(self.cursor),
+            1.0,
+            touch,
+        );
+        if let Some(ev) = TouchEvent::from_touch_args(&args, e) {
+            f(&ev);
+        }
+    }
+
+    /// Handles an event, calling the closure with the event and emulated touch events.
+    ///
+    /// Emulated events are emitted after the mouse event.
+    /// Losing focus or disabling the adapter cancels the touch.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(&E),
+    {
+        if let Some(size) = window_size(e) {
+            self.window_size = size;
+        }
+        f(e);
+        if let Some(pos) = e.mouse_cursor_args() {
+            self.cursor = pos;
+        }
+        if !self.enabled || e.focus_args() == Some(false) {
+            if self.touching {
+                self.touching = false;
+                self.emit(e, Touch::Cancel, &mut f);
+            }
+            return;
+        }
+        if e.mouse_cursor_args().is_some() && self.touching {
+            self.emit(e, Touch::Move, &mut f);
+        }
+        if let Some(args) = e.button_args() {
+            if args.button != Button::Mouse(self.button) {
+                return;
+            }
+            match args.state {
+                ButtonState::Press if !self.touching => {
+                    self.touching = true;
+                    self.emit(e, Touch::Start, &mut f);
+                }
+                ButtonState::Release if self.touching => {
+                    self.touching = false;
+                    self.emit(e, Touch::End, &mut f);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Event, Input, Motion, ResizeArgs, UpdateArgs};
+
+    fn touch(touch: Touch, position: [f64; 2], time: TimeStamp) -> Event {
+        let args = TouchArgs::new(0, 0, position, 1.0, touch);
+        Event::Input(Input::Move(Motion::Touch(args)), Some(time))
+    }
+
+    fn resize() -> Event {
+        Event::Input(
+            Input::Resize(ResizeArgs {
+                window_size: [200.0, 100.0],
+                draw_size: [200, 100],
+            }),
+            None,
+        )
+    }
+
+    fn buttons(events: &[Event]) -> Vec<(ButtonState, Button)> {
+        events
+            .iter()
+            .filter_map(|e| e.button_args())
+            .map(|args| (args.state, args.button))
+            .collect()
+    }
+
+    #[test]
+    fn test_touch_to_mouse() {
+        use ButtonState::*;
+
+        let mut adapter = TouchToMouse::new(TouchToMouseSettings::new());
+        let mut events = vec![];
+        for e in &[
+            resize(),
+            touch(Touch::Start, [0.5, 0.5], 0),
+            touch(Touch::End, [0.5, 0.5], 100),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        let left = Button::Mouse(MouseButton::Left);
+        assert_eq!(buttons(&events), vec![(Press, left), (Release, left)]);
+        assert_eq!(
+            events.iter().rev().find_map(|e| e.mouse_cursor_args()),
+            Some([100.0, 50.0])
+        );
+
+        events.clear();
+        for e in &[
+            touch(Touch::Start, [0.5, 0.5], 200),
+            touch(Touch::Move, [0.6, 0.5], 250),
+            touch(Touch::End, [0.7, 0.5], 300),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        assert_eq!(buttons(&events), vec![(Press, left), (Release, left)]);
+        // Press is emitted when the touch moves.
+        assert_eq!(events[3].button_args().unwrap().state, Press);
+        assert_eq!(events[4].mouse_cursor_args(), Some([120.0, 50.0]));
+
+        events.clear();
+        let update: Event = UpdateArgs { dt: 0.6 }.into();
+        for e in &[
+            touch(Touch::Start, [0.5, 0.5], 400),
+            update,
+            touch(Touch::End, [0.5, 0.5], 1100),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        let right = Button::Mouse(MouseButton::Right);
+        assert_eq!(buttons(&events), vec![(Press, right), (Release, right)]);
+
+        adapter.enabled = false;
+        events.clear();
+        adapter.event(&touch(Touch::Start, [0.5, 0.5], 1200), |e| {
+            events.push(e.clone())
+        });
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_touch_to_mouse_long_press_without_update() {
+        use ButtonState::*;
+
+        let right = Button::Mouse(MouseButton::Right);
+        let mut adapter = TouchToMouse::new(TouchToMouseSettings::new());
+        let mut events = vec![];
+        for e in &[
+            resize(),
+            touch(Touch::Start, [0.5, 0.5], 0),
+            touch(Touch::End, [0.5, 0.5], 600),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        assert_eq!(buttons(&events), vec![(Press, right), (Release, right)]);
+
+        // A small move after the long press time emits the right click while held.
+        events.clear();
+        for e in &[
+            touch(Touch::Start, [0.5, 0.5], 1000),
+            touch(Touch::Move, [0.51, 0.5], 1600),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        assert_eq!(buttons(&events), vec![(Press, right), (Release, right)]);
+        events.clear();
+        adapter.event(&touch(Touch::End, [0.51, 0.5], 1700), |e| {
+            events.push(e.clone())
+        });
+        assert_eq!(buttons(&events), vec![]);
+    }
+
+    #[test]
+    fn test_touch_to_mouse_reset() {
+        use ButtonState::*;
+
+        let left = Button::Mouse(MouseButton::Left);
+        let mut adapter = TouchToMouse::new(TouchToMouseSettings::new());
+        let mut events = vec![];
+        for e in &[
+            resize(),
+            touch(Touch::Start, [0.5, 0.5], 0),
+            touch(Touch::Move, [0.7, 0.5], 50),
+            Event::Input(Input::Focus(false), Some(60)),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        assert_eq!(buttons(&events), vec![(Press, left), (Release, left)]);
+        assert!(!adapter.is_dragging());
+
+        // The end of the touch is lost, so the next start replaces it.
+        events.clear();
+        for e in &[
+            touch(Touch::Start, [0.5, 0.5], 100),
+            touch(Touch::Move, [0.7, 0.5], 150),
+            touch(Touch::Start, [0.2, 0.5], 200),
+            touch(Touch::End, [0.2, 0.5], 250),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        assert_eq!(
+            buttons(&events),
+            vec![
+                (Press, left),
+                (Release, left),
+                (Press, left),
+                (Release, left)
+            ]
+        );
+        assert_eq!(
+            events.iter().rev().find_map(|e| e.mouse_cursor_args()),
+            Some([40.0, 50.0])
+        );
+        assert!(!adapter.is_dragging());
+    }
+
+    #[test]
+    fn test_mouse_to_touch() {
+        use crate::MouseButton::Left;
+
+        let mut adapter = MouseToTouch::new();
+        let mut events = vec![];
+        let press = ButtonArgs::new(ButtonState::Press, Button::Mouse(Left), None);
+        let release = ButtonArgs::new(ButtonState::Release, Button::Mouse(Left), None);
+        for e in &[
+            resize(),
+            Event::Input(Input::Move(Motion::MouseCursor([50.0, 50.0])), None),
+            Event::Input(Input::Button(press), None),
+            Event::Input(Input::Move(Motion::MouseCursor([100.0, 25.0])), None),
+            Event::Input(Input::Button(release), None),
+        ] {
+            adapter.event(e, |e| events.push(e.clone()));
+        }
+        let touches: Vec<TouchArgs> = events.iter().filter_map(|e| e.touch_args()).collect();
+        assert_eq!(
+            touches,
+            vec![
+                TouchArgs::new(MOUSE_TOUCH_DEVICE, 0, [0.25, 0.5], 1.0, Touch::Start),
+                TouchArgs::new(MOUSE_TOUCH_DEVICE, 0, [0.5, 0.25], 1.0, Touch::Move),
+                TouchArgs::new(MOUSE_TOUCH_DEVICE, 0, [0.5, 0.25], 1.0, Touch::End),
+            ]
+        );
+
+        // Emulated touches are not converted back to the mouse.
+        let mut touch_to_mouse = TouchToMouse::new(TouchToMouseSettings::new());
+        let mut mouse_events = 0;
+        for e in &events {
+            touch_to_mouse.event(e, |_| mouse_events += 1);
+        }
+        assert_eq!(mouse_events, events.len());
+    }
+}
@@ -1,29 +1,51 @@
 //! Event identifiers.
 
+use std::{collections::BTreeMap, error::Error, fmt, sync::RwLock};
+
 /// Event id for after render event.
 pub const AFTER_RENDER: EventId = EventId("piston/after_render");
 /// Event id for controller axis event.
 pub const CONTROLLER_AXIS: EventId = EventId("piston/controller_axis");
+/// Event id for controller connection event.
+pub const CONTROLLER_CONNECTION: EventId = EventId("piston/controller_connection");
 /// Event id for cursor event.
 pub const CURSOR: EventId = EventId("piston/cursor");
 /// Event id for focus event.
 pub const FOCUS: EventId = EventId("piston/focus");
 /// Event id for close event.
 pub const CLOSE: EventId = EventId("piston/close");
+/// Event id for IME event.
+pub const IME: EventId = EventId("piston/ime");
+/// Event id for gesture event.
+pub const GESTURE: EventId = EventId("piston/gesture");
 /// Event id for idle event.
 pub const IDLE: EventId = EventId("piston/idle");
 /// Event id for mouse scroll event.
 pub const MOUSE_SCROLL: EventId = EventId("piston/mouse_scroll");
 /// Event id for mouse relative event.
 pub const MOUSE_RELATIVE: EventId = EventId("piston/mouse_relative");
+/// Event id for maximized event.
+pub const MAXIMIZED: EventId = EventId("piston/maximized");
+/// Event id for minimized event.
+pub const MINIMIZED: EventId = EventId("piston/minimized");
 /// Event id for mouse cursor event.
 pub const MOUSE_CURSOR: EventId = EventId("piston/mouse_cursor");
+/// Event id for moved event.
+pub const MOVED: EventId = EventId("piston/moved");
+/// Event id for occluded event.
+pub const OCCLUDED: EventId = EventId("piston/occluded");
+/// Event id for pen event.
+pub const PEN: EventId = EventId("piston/pen");
 /// Event id for button event.
 pub const BUTTON: EventId = EventId("piston/button");
 /// Event id for render event.
 pub const RENDER: EventId = EventId("piston/render");
 /// Event id for resize event.
 pub const RESIZE: EventId = EventId("piston/resize");
+/// Event id for scale factor event.
+pub const SCALE_FACTOR: EventId = EventId("piston/scale_factor");
+/// Event id for scroll event.
+pub const SCROLL: EventId = EventId("piston/scroll");
 /// Event id for text event.
 pub const TEXT: EventId = EventId("piston/text");
 /// Event id for touch event.
@@ -33,8 +55,184 @@ pub const UPDATE: EventId = EventId("piston/update");
 /// Event id for file drag event.
 pub const FILE_DRAG: EventId = EventId("piston/file_drag");
 
+/// The event ids of events provided by Piston.
+pub const BUILTIN: &[EventId] = &[
+    AFTER_RENDER,
+    BUTTON,
+    CLOSE,
+    CONTROLLER_AXIS,
+    CONTROLLER_CONNECTION,
+    CURSOR,
+    FILE_DRAG,
+    FOCUS,
+    GESTURE,
+    IDLE,
+    IME,
+    MAXIMIZED,
+    MINIMIZED,
+    MOUSE_CURSOR,
+    MOUSE_RELATIVE,
+    MOUSE_SCROLL,
+    MOVED,
+    OCCLUDED,
+    PEN,
+    RENDER,
+    RESIZE,
+    SCALE_FACTOR,
+    SCROLL,
+    TEXT,
+    TOUCH,
+    UPDATE,
+];
+
 /// Used to identify events arguments provided by traits.
 ///
 /// Use format `<api>/<event>` to avoid naming collision.
+///
+/// Event ids created at runtime, e.g. by scripts or plugins,
+/// can be created with [`EventId::intern`] or [`EventId::register`].
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct EventId(pub &'static str);
+
+/// Stores interned names, and whether they are registered.
+static INTERNED: RwLock<BTreeMap<&'static str, bool>> = RwLock::new(BTreeMap::new());
+
+impl EventId {
+    /// Returns the name of the event id.
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+
+    /// Creates an event id from a name created at runtime.
+    ///
+    /// Each unique name is allocated only once,
+    /// so calling this repeatedly with the same name does not leak memory.
+    pub fn intern(name: &str) -> EventId {
+        if let Some((&name, _)) = INTERNED
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get_key_value(name)
+        {
+            return EventId(name);
+        }
+        let mut interned = INTERNED.write().unwrap_or_else(|err| err.into_inner());
+        if let Some((&name, _)) = interned.get_key_value(name) {
+            return EventId(name);
+        }
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+        interned.insert(name, false);
+        EventId(name)
+    }
+
+    /// Registers a new event id created at runtime.
+    ///
+    /// The name must use the format `<api>/<event>`.
+    /// Returns an error if the name is invalid,
+    /// already registered or used by an event provided by Piston.
+    pub fn register(name: &str) -> Result<EventId, EventIdError> {
+        let mut parts = name.splitn(2, '/');
+        let valid = match (parts.next(), parts.next()) {
+            (Some(api), Some(event)) => !api.is_empty() && !event.is_empty(),
+            _ => false,
+        };
+        if !valid {
+            return Err(EventIdError::InvalidName(name.into()));
+        }
+        if let Some(&id) = BUILTIN.iter().find(|id| id.0 == name) {
+            return Err(EventIdError::Duplicate(id));
+        }
+        let id = EventId::intern(name);
+        let mut interned = INTERNED.write().unwrap_or_else(|err| err.into_inner());
+        let registered = interned.entry(id.0).or_insert(false);
+        if *registered {
+            return Err(EventIdError::Duplicate(id));
+        }
+        *registered = true;
+        Ok(id)
+    }
+
+    /// Returns `true` if the event id is registered or provided by Piston.
+    pub fn is_registered(&self) -> bool {
+        BUILTIN.contains(self)
+            || INTERNED
+                .read()
+                .unwrap_or_else(|err| err.into_inner())
+                .get(self.0)
+                .copied()
+                .unwrap_or(false)
+    }
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An error when registering an event id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EventIdError {
+    /// The name does not use the format `<api>/<event>`.
+    InvalidName(String),
+    /// The event id is already registered.
+    Duplicate(EventId),
+}
+
+impl fmt::Display for EventIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            EventIdError::InvalidName(ref name) => write!(
+                f,
+                "Invalid event id `{}`, expected format `<api>/<event>`",
+                name
+            ),
+            EventIdError::Duplicate(id) => write!(f, "Event id `{}` is already registered", id),
+        }
+    }
+}
+
+impl Error for EventIdError {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{Event, GenericEvent};
+
+    #[test]
+    fn test_intern() {
+        let name = String::from("test/intern");
+        let a = EventId::intern(&name);
+        let b = EventId::intern("test/intern");
+        assert_eq!(a, b);
+        assert!(std::ptr::eq(a.0, b.0));
+        assert_eq!(a, EventId("test/intern"));
+        assert!(!a.is_registered());
+
+        let e = Event::Custom(a, Arc::new(()), None);
+        assert_eq!(e.event_id(), EventId("test/intern"));
+        let f = Event::Custom(EventId::intern("test/other"), Arc::new(()), None);
+        assert!(e < f);
+    }
+
+    #[test]
+    fn test_register() {
+        let id = EventId::register("test/register").unwrap();
+        assert!(id.is_registered());
+        assert_eq!(
+            EventId::register("test/register"),
+            Err(EventIdError::Duplicate(id))
+        );
+        assert_eq!(
+            EventId::register("piston/update"),
+            Err(EventIdError::Duplicate(UPDATE))
+        );
+        assert_eq!(
+            EventId::register("register"),
+            Err(EventIdError::InvalidName("register".into()))
+        );
+        assert!(EventId::register("/register").is_err());
+        assert!(UPDATE.is_registered());
+    }
+}
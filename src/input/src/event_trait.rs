@@ -0,0 +1,266 @@
+//! Macro for defining event traits.
+
+/// Defines an event trait and implements it for `Event`.
+///
+/// The trait has the same methods as the built-in event traits:
+/// a constructor preserving the time stamp of the original event,
+/// an accessor calling a closure with the arguments,
+/// and a method returning a copy of the arguments.
+///
+/// Arguments declared as a reference, e.g. `args: &ResizeArgs`, are passed by reference
+/// and must implement `Clone`.
+/// Other arguments, e.g. `args: [i32; 2]`, are passed by value and must implement `Copy`.
+///
+/// The event is stored in one of these variants:
+///
+/// - `Input(<path>, ...)`, e.g. `Input(Input::Resize)`, or nested `Input(Input::Move, Motion::Pen)`
+/// - `Loop(<path>)`, e.g. `Loop(Loop::Update)`
+/// - `Custom`, using the event id of the arguments, which must implement `CustomArgs`
+///
+/// Like the built-in event traits, the accessor and arguments return `Option`.
+/// Custom events with the same event id, but arguments of another type, return `None`.
+/// To report these, name a method after `checked_args_getter`,
+/// which returns `Result<Option<_>, CustomTypeError>`
+/// like [`CustomEvent::custom`](crate::CustomEvent::custom).
+///
+/// The arguments must implement `PartialEq` and `Debug`.
+/// A round-trip test is generated with the name and example arguments after `test`.
+///
+/// ```ignore
+/// #[derive(Clone, Debug, PartialEq)]
+/// pub struct GyroArgs {
+///     pub id: u32,
+///     pub rotation: [f64; 3],
+/// }
+///
+/// impl CustomArgs for GyroArgs {
+///     fn event_id() -> EventId {
+///         EventId("gamepad/gyro")
+///     }
+/// }
+///
+/// input::event_trait! {
+///     /// When the gyroscope of a gamepad moves.
+///     pub trait GyroEvent {
+///         args: &GyroArgs,
+///         from_args: from_gyro_args,
+///         accessor: gyro,
+///         args_getter: gyro_args,
+///         checked_args_getter: checked_gyro_args,
+///         event: Custom,
+///         test: test_input_gyro(GyroArgs { id: 0, rotation: [0.0, 1.0, 0.0] }),
+///     }
+/// }
+/// ```
+///
+/// Since the trait is not a super trait of `GenericEvent`,
+/// generic code uses it with an extra bound, e.g. `E: GenericEvent + GyroEvent`.
+#[macro_export]
+macro_rules! event_trait {
+    (
+        $(#[$attr:meta])*
+        $vis:vis trait $trait:ident {
+            args: & $args:ty,
+            from_args: $from_args:ident,
+            accessor: $accessor:ident,
+            args_getter: $args_getter:ident,
+            $(checked_args_getter: $checked:ident,)?
+            event: $kind:ident $(($($event:tt)*))?,
+            test: $test:ident($example:expr) $(,)?
+        }
+    ) => {
+        $crate::event_trait! {
+            @define ref,
+            $(#[$attr])*
+            $vis trait $trait {
+                args: $args,
+                from_args: $from_args,
+                accessor: $accessor,
+                args_getter: $args_getter,
+                checked: [$($checked)?],
+                event: $kind($($($event)*)?),
+                test: $test($example),
+            }
+        }
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis trait $trait:ident {
+            args: $args:ty,
+            from_args: $from_args:ident,
+            accessor: $accessor:ident,
+            args_getter: $args_getter:ident,
+            $(checked_args_getter: $checked:ident,)?
+            event: $kind:ident $(($($event:tt)*))?,
+            test: $test:ident($example:expr) $(,)?
+        }
+    ) => {
+        $crate::event_trait! {
+            @define val,
+            $(#[$attr])*
+            $vis trait $trait {
+                args: $args,
+                from_args: $from_args,
+                accessor: $accessor,
+                args_getter: $args_getter,
+                checked: [$($checked)?],
+                event: $kind($($($event)*)?),
+                test: $test($example),
+            }
+        }
+    };
+    (
+        @define $mode:ident,
+        $(#[$attr:meta])*
+        $vis:vis trait $trait:ident {
+            args: $args:ty,
+            from_args: $from_args:ident,
+            accessor: $accessor:ident,
+            args_getter: $args_getter:ident,
+            checked: [$($checked:ident)?],
+            event: $kind:ident($($event:tt)*),
+            test: $test:ident($example:expr),
+        }
+    ) => {
+        $(#[$attr])*
+        $vis trait $trait: Sized {
+            /// Creates an event from arguments.
+            ///
+            /// Preserves time stamp from original event, if any.
+            fn $from_args(
+                args: $crate::event_trait!(@arg $mode, $args),
+                old_event: &Self,
+            ) -> Option<Self>;
+            /// Calls closure if this is an event of this kind.
+            fn $accessor<U, F>(&self, f: F) -> Option<U>
+            where
+                F: FnMut($crate::event_trait!(@arg $mode, $args)) -> U;
+            /// Returns the arguments.
+            fn $args_getter(&self) -> Option<$args> {
+                self.$accessor(|args| $crate::event_trait!(@own $mode, args))
+            }
+            $(
+                /// Returns the arguments,
+                /// or an error if a custom event with the same event id has arguments of another type.
+                fn $checked(&self) -> Result<Option<$args>, $crate::CustomTypeError>;
+            )?
+        }
+
+        impl $trait for $crate::Event {
+            fn $from_args(
+                args: $crate::event_trait!(@arg $mode, $args),
+                old_event: &Self,
+            ) -> Option<Self> {
+                $crate::event_trait!(@new $mode, $kind($($event)*), args, old_event)
+            }
+
+            fn $accessor<U, F>(&self, mut f: F) -> Option<U>
+            where
+                F: FnMut($crate::event_trait!(@arg $mode, $args)) -> U,
+            {
+                $crate::event_trait!(@get $mode, $kind($($event)*), $args, self, f)
+            }
+
+            $(
+                fn $checked(&self) -> Result<Option<$args>, $crate::CustomTypeError> {
+                    $crate::event_trait!(@checked $mode, $kind, $args, self)
+                }
+            )?
+        }
+
+        #[cfg(test)]
+        #[test]
+        fn $test() {
+            use $crate::{Event, GenericEvent, Input};
+
+            let args: $args = $example;
+            let old_event = Event::Input(Input::Focus(true), Some(1));
+            let x: Option<Event> =
+                $trait::$from_args($crate::event_trait!(@ref $mode, args), &old_event);
+            let x = x.unwrap();
+            let y: Option<Event> = x.$accessor(|args| $trait::$from_args(args, &x)).unwrap();
+            let y = y.unwrap();
+            assert_eq!(x.$args_getter(), Some($crate::event_trait!(@own $mode, args)));
+            $(assert_eq!(x.$checked(), Ok(Some($crate::event_trait!(@own $mode, args))));)?
+            assert_eq!(y.$args_getter(), Some(args));
+            assert_eq!(
+                x.time_stamp(),
+                $crate::event_trait!(@time_stamp $kind, Some(1))
+            );
+            assert_eq!(x.event_id(), y.event_id());
+        }
+    };
+    (@arg ref, $args:ty) => { &$args };
+    (@arg val, $args:ty) => { $args };
+    (@own ref, $a:ident) => { $a.clone() };
+    (@own val, $a:ident) => { $a };
+    (@ref ref, $a:ident) => { &$a };
+    (@ref val, $a:ident) => { $a };
+    (@pass ref, $a:ident) => { $a };
+    (@pass val, $a:ident) => { *$a };
+    (@copy ref, $a:ident) => { $a.clone() };
+    (@copy val, $a:ident) => { *$a };
+    (@new $mode:ident, Input($($variant:path),+), $a:ident, $old_event:ident) => {{
+        let timestamp = if let $crate::Event::Input(_, x) = *$old_event {
+            x
+        } else {
+            None
+        };
+        Some($crate::Event::Input(
+            $crate::event_trait!(@wrap [$($variant),+], $crate::event_trait!(@own $mode, $a)),
+            timestamp,
+        ))
+    }};
+    (@new $mode:ident, Loop($variant:path), $a:ident, $old_event:ident) => {{
+        let _ = $old_event;
+        Some($crate::Event::Loop($variant($crate::event_trait!(@own $mode, $a))))
+    }};
+    (@new $mode:ident, Custom(), $a:ident, $old_event:ident) => {
+        $crate::CustomEvent::from_custom_args($crate::event_trait!(@own $mode, $a), $old_event)
+    };
+    (@get $mode:ident, Input($($variant:path),+), $args:ty, $e:ident, $f:ident) => {
+        match *$e {
+            $crate::Event::Input($crate::event_trait!(@pat [$($variant),+], args), _) => {
+                Some($f($crate::event_trait!(@pass $mode, args)))
+            }
+            _ => None,
+        }
+    };
+    (@get $mode:ident, Loop($variant:path), $args:ty, $e:ident, $f:ident) => {
+        match *$e {
+            $crate::Event::Loop($variant(ref args)) => {
+                Some($f($crate::event_trait!(@pass $mode, args)))
+            }
+            _ => None,
+        }
+    };
+    (@get $mode:ident, Custom(), $args:ty, $e:ident, $f:ident) => {
+        match $crate::CustomEvent::custom::<$args>($e) {
+            Ok(Some(args)) => {
+                let args: &$args = &args;
+                Some($f($crate::event_trait!(@pass $mode, args)))
+            }
+            _ => None,
+        }
+    };
+    (@checked $mode:ident, Custom, $args:ty, $e:ident) => {
+        Ok($crate::CustomEvent::custom::<$args>($e)?.map(|args| {
+            let args: &$args = &args;
+            $crate::event_trait!(@copy $mode, args)
+        }))
+    };
+    (@wrap [$variant:path], $e:expr) => { $variant($e) };
+    (@wrap [$variant:path, $($rest:path),+], $e:expr) => {
+        $variant($crate::event_trait!(@wrap [$($rest),+], $e))
+    };
+    (@pat [$variant:path], $a:ident) => { $variant(ref $a) };
+    (@pat [$variant:path, $($rest:path),+], $a:ident) => {
+        $variant($crate::event_trait!(@pat [$($rest),+], $a))
+    };
+    (@time_stamp Loop, $time_stamp:expr) => {
+        None
+    };
+    (@time_stamp $kind:ident, $time_stamp:expr) => {
+        $time_stamp
+    };
+}
@@ -0,0 +1,797 @@
+//! Standard gamepad layout on top of raw controller events.
+//!
+//! Raw controller buttons and axes are numbered differently for every device.
+//! A [`MappingDatabase`] stores mappings in the format used by SDL's
+//! [`gamecontrollerdb.txt`](https://github.com/gabomdq/SDL_GameControllerDB),
+//! keyed by the GUID of the controller.
+//! [`Gamepads`] uses these mappings to convert raw controller events
+//! into named [`GamepadButton`] and [`GamepadAxis`] events.
+
+use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+
+use crate::{Button, ButtonState, ConnectionState, ControllerButton, GenericEvent, HatState};
+
+/// Buttons of a standard gamepad.
+///
+/// Face buttons are named by their position,
+/// since the labels differ between vendors.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum GamepadButton {
+    /// Bottom face button (A on Xbox, cross on Sony controllers).
+    South,
+    /// Right face button (B on Xbox, circle on Sony controllers).
+    East,
+    /// Left face button (X on Xbox, square on Sony controllers).
+    West,
+    /// Top face button (Y on Xbox, triangle on Sony controllers).
+    North,
+    /// Select or back button.
+    Select,
+    /// Vendor button in the center.
+    Guide,
+    /// Start button.
+    Start,
+    /// Pressing the left stick.
+    LeftStick,
+    /// Pressing the right stick.
+    RightStick,
+    /// Left shoulder button.
+    LeftShoulder,
+    /// Right shoulder button.
+    RightShoulder,
+    /// D-pad up.
+    DPadUp,
+    /// D-pad down.
+    DPadDown,
+    /// D-pad left.
+    DPadLeft,
+    /// D-pad right.
+    DPadRight,
+    /// Extra button, e.g. share or capture.
+    Misc1,
+    /// Upper right paddle.
+    Paddle1,
+    /// Upper left paddle.
+    Paddle2,
+    /// Lower right paddle.
+    Paddle3,
+    /// Lower left paddle.
+    Paddle4,
+    /// Touchpad button.
+    Touchpad,
+}
+
+impl GamepadButton {
+    /// Returns the button from its name in SDL mapping strings.
+    pub fn from_sdl_name(name: &str) -> Option<GamepadButton> {
+        use GamepadButton::*;
+
+        Some(match name {
+            "a" => South,
+            "b" => East,
+            "x" => West,
+            "y" => North,
+            "back" => Select,
+            "guide" => Guide,
+            "start" => Start,
+            "leftstick" => LeftStick,
+            "rightstick" => RightStick,
+            "leftshoulder" => LeftShoulder,
+            "rightshoulder" => RightShoulder,
+            "dpup" => DPadUp,
+            "dpdown" => DPadDown,
+            "dpleft" => DPadLeft,
+            "dpright" => DPadRight,
+            "misc1" => Misc1,
+            "paddle1" => Paddle1,
+            "paddle2" => Paddle2,
+            "paddle3" => Paddle3,
+            "paddle4" => Paddle4,
+            "touchpad" => Touchpad,
+            _ => return None,
+        })
+    }
+}
+
+/// Axes of a standard gamepad.
+///
+/// Sticks are normalized to [-1.0, 1.0], with positive y pointing down.
+/// Triggers are normalized to [0.0, 1.0].
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum GamepadAxis {
+    /// Horizontal position of left stick.
+    LeftX,
+    /// Vertical position of left stick.
+    LeftY,
+    /// Horizontal position of right stick.
+    RightX,
+    /// Vertical position of right stick.
+    RightY,
+    /// Left trigger.
+    LeftTrigger,
+    /// Right trigger.
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    /// Returns the axis from its name in SDL mapping strings.
+    pub fn from_sdl_name(name: &str) -> Option<GamepadAxis> {
+        use GamepadAxis::*;
+
+        Some(match name {
+            "leftx" => LeftX,
+            "lefty" => LeftY,
+            "rightx" => RightX,
+            "righty" => RightY,
+            "lefttrigger" => LeftTrigger,
+            "righttrigger" => RightTrigger,
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` if this is a trigger.
+    pub fn is_trigger(&self) -> bool {
+        matches!(*self, GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger)
+    }
+}
+
+/// The part of an axis that is used by a mapping.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum AxisRange {
+    /// The whole axis.
+    Full,
+    /// The positive half of the axis.
+    Positive,
+    /// The negative half of the axis.
+    Negative,
+}
+
+/// A raw controller input used by a mapping.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum MappingSource {
+    /// A raw controller button.
+    Button(u8),
+    /// A raw controller axis.
+    Axis {
+        /// The raw axis.
+        axis: u8,
+        /// The part of the axis that is used.
+        range: AxisRange,
+        /// Whether the axis is inverted.
+        invert: bool,
+    },
+    /// A direction of a raw controller hat.
+    Hat {
+        /// The raw hat.
+        hat: u8,
+        /// The direction bit mask, 1 = up, 2 = right, 4 = down, 8 = left.
+        mask: u8,
+    },
+}
+
+/// A standard gamepad input produced by a mapping.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum MappingTarget {
+    /// A gamepad button.
+    Button(GamepadButton),
+    /// A gamepad axis or half axis.
+    Axis(GamepadAxis, AxisRange),
+}
+
+/// An error when parsing a mapping string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MappingError {
+    /// The mapping has no GUID.
+    MissingGuid,
+    /// The mapping has no name.
+    MissingName,
+    /// A binding could not be parsed.
+    InvalidBinding(String),
+}
+
+impl fmt::Display for MappingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MappingError::MissingGuid => write!(f, "Mapping has no GUID"),
+            MappingError::MissingName => write!(f, "Mapping has no name"),
+            MappingError::InvalidBinding(ref binding) => {
+                write!(f, "Invalid mapping binding `{}`", binding)
+            }
+        }
+    }
+}
+
+impl Error for MappingError {}
+
+/// A mapping from raw controller inputs to a standard gamepad.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mapping {
+    /// The GUID of the controller.
+    pub guid: String,
+    /// The human readable name of the controller.
+    pub name: String,
+    /// The platform this mapping is for, if any.
+    pub platform: Option<String>,
+    /// Maps raw controller inputs to gamepad inputs.
+    pub bindings: Vec<(MappingSource, MappingTarget)>,
+}
+
+fn split_range(s: &str) -> (AxisRange, &str) {
+    if let Some(rest) = s.strip_prefix('+') {
+        (AxisRange::Positive, rest)
+    } else if let Some(rest) = s.strip_prefix('-') {
+        (AxisRange::Negative, rest)
+    } else {
+        (AxisRange::Full, s)
+    }
+}
+
+fn parse_target(s: &str) -> Option<MappingTarget> {
+    let (range, name) = split_range(s);
+    if let Some(button) = GamepadButton::from_sdl_name(name) {
+        if range == AxisRange::Full {
+            Some(MappingTarget::Button(button))
+        } else {
+            None
+        }
+    } else {
+        GamepadAxis::from_sdl_name(name).map(|axis| MappingTarget::Axis(axis, range))
+    }
+}
+
+fn parse_source(s: &str) -> Option<MappingSource> {
+    let (range, s) = split_range(s);
+    let (s, invert) = match s.strip_suffix('~') {
+        Some(s) => (s, true),
+        None => (s, false),
+    };
+    if let Some(axis) = s.strip_prefix('a') {
+        Some(MappingSource::Axis {
+            axis: axis.parse().ok()?,
+            range,
+            invert,
+        })
+    } else if range != AxisRange::Full || invert {
+        None
+    } else if let Some(button) = s.strip_prefix('b') {
+        Some(MappingSource::Button(button.parse().ok()?))
+    } else if let Some(hat) = s.strip_prefix('h') {
+        let mut parts = hat.splitn(2, '.');
+        let hat = parts.next()?.parse().ok()?;
+        let mask = parts.next()?.parse().ok()?;
+        Some(MappingSource::Hat { hat, mask })
+    } else {
+        None
+    }
+}
+
+impl FromStr for Mapping {
+    type Err = MappingError;
+
+    /// Parses a mapping string, for example
+    /// `03000000de280000ff11000001000000,Steam Virtual Gamepad,a:b0,b:b1,...,platform:Linux,`.
+    fn from_str(s: &str) -> Result<Mapping, MappingError> {
+        let mut parts = s.trim().split(',');
+        let guid = match parts.next() {
+            Some(guid) if !guid.is_empty() => guid.to_lowercase(),
+            _ => return Err(MappingError::MissingGuid),
+        };
+        let name = match parts.next() {
+            Some(name) if !name.is_empty() => name.into(),
+            _ => return Err(MappingError::MissingName),
+        };
+        let mut platform = None;
+        let mut bindings = vec![];
+        for binding in parts {
+            if binding.is_empty() {
+                continue;
+            }
+            let invalid = || MappingError::InvalidBinding(binding.into());
+            let mut kv = binding.splitn(2, ':');
+            let key = kv.next().ok_or_else(invalid)?;
+            let value = kv.next().ok_or_else(invalid)?;
+            if key == "platform" {
+                platform = Some(value.into());
+                continue;
+            }
+            if value.is_empty() {
+                // Unbound input.
+                continue;
+            }
+            // Ignore fields that are not part of the standard layout,
+            // to support mappings from newer versions of the database.
+            if let Some(target) = parse_target(key) {
+                bindings.push((parse_source(value).ok_or_else(invalid)?, target));
+            }
+        }
+        Ok(Mapping {
+            guid,
+            name,
+            platform,
+            bindings,
+        })
+    }
+}
+
+/// Stores gamepad mappings keyed by controller GUID.
+#[derive(Clone, Debug, Default)]
+pub struct MappingDatabase {
+    /// When set, mappings for other platforms are ignored.
+    ///
+    /// Uses the platform names of SDL, e.g. `Windows`, `Mac OS X` or `Linux`.
+    pub platform: Option<String>,
+    mappings: HashMap<String, Mapping>,
+}
+
+impl MappingDatabase {
+    /// Creates a new empty database that accepts mappings for all platforms.
+    pub fn new() -> MappingDatabase {
+        MappingDatabase::default()
+    }
+
+    /// Adds a mapping, replacing any previous mapping for the same GUID.
+    ///
+    /// Returns `false` if the mapping was ignored because of its platform.
+    pub fn add(&mut self, mapping: Mapping) -> bool {
+        if let (Some(a), Some(b)) = (&self.platform, &mapping.platform) {
+            if a != b {
+                return false;
+            }
+        }
+        self.mappings.insert(mapping.guid.clone(), mapping);
+        true
+    }
+
+    /// Parses and adds a single mapping string.
+    pub fn add_mapping(&mut self, s: &str) -> Result<bool, MappingError> {
+        Ok(self.add(s.parse()?))
+    }
+
+    /// Parses and adds mappings from the contents of a `gamecontrollerdb.txt` file.
+    ///
+    /// Empty lines and comments starting with `#` are skipped.
+    /// Returns the number of mappings added.
+    pub fn add_mappings(&mut self, text: &str) -> Result<usize, MappingError> {
+        let mut n = 0;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if self.add_mapping(line)? {
+                n += 1;
+            }
+        }
+        Ok(n)
+    }
+
+    /// Gets the mapping of a controller GUID.
+    ///
+    /// The GUID is compared case insensitively.
+    pub fn get(&self, guid: &str) -> Option<&Mapping> {
+        self.mappings.get(&guid.to_lowercase())
+    }
+
+    /// Returns the number of mappings.
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Returns `true` if there are no mappings.
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+}
+
+/// A change of a standard gamepad input.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub enum GamepadEvent {
+    /// A gamepad button changed state.
+    Button {
+        /// Which controller the button is on.
+        id: u32,
+        /// The gamepad button.
+        button: GamepadButton,
+        /// The new state of the button.
+        state: ButtonState,
+    },
+    /// A gamepad axis moved.
+    Axis {
+        /// Which controller the axis is on.
+        id: u32,
+        /// The gamepad axis.
+        axis: GamepadAxis,
+        /// The normalized position of the axis.
+        position: f64,
+    },
+}
+
+/// Stores state of a mapped controller.
+#[derive(Clone, Debug)]
+struct Gamepad {
+    mapping: Mapping,
+    buttons: Vec<GamepadButton>,
+    axes: HashMap<GamepadAxis, f64>,
+    hats: HashMap<u8, u8>,
+}
+
+impl Gamepad {
+    fn set_button<F>(&mut self, id: u32, button: GamepadButton, pressed: bool, f: &mut F)
+    where
+        F: FnMut(GamepadEvent),
+    {
+        let index = self.buttons.iter().position(|&b| b == button);
+        let state = match (pressed, index) {
+            (true, None) => {
+                self.buttons.push(button);
+                ButtonState::Press
+            }
+            (false, Some(i)) => {
+                self.buttons.swap_remove(i);
+                ButtonState::Release
+            }
+            _ => return,
+        };
+        f(GamepadEvent::Button { id, button, state });
+    }
+
+    fn set_axis<F>(&mut self, id: u32, axis: GamepadAxis, position: f64, f: &mut F)
+    where
+        F: FnMut(GamepadEvent),
+    {
+        let min = if axis.is_trigger() { 0.0 } else { -1.0 };
+        let position = position.max(min).min(1.0);
+        self.axes.insert(axis, position);
+        f(GamepadEvent::Axis { id, axis, position });
+    }
+
+    /// Applies a source value in [-1.0, 1.0] for full sources or [0.0, 1.0] for half sources.
+    fn apply<F>(&mut self, id: u32, full: bool, value: f64, target: MappingTarget, f: &mut F)
+    where
+        F: FnMut(GamepadEvent),
+    {
+        // Normalize value to [0.0, 1.0].
+        let magnitude = if full { (value + 1.0) / 2.0 } else { value };
+        match target {
+            MappingTarget::Button(button) => self.set_button(id, button, magnitude > 0.5, f),
+            MappingTarget::Axis(axis, AxisRange::Full) => {
+                if axis.is_trigger() {
+                    self.set_axis(id, axis, magnitude, f);
+                } else if full {
+                    self.set_axis(id, axis, value, f);
+                } else {
+                    self.set_axis(id, axis, 2.0 * value - 1.0, f);
+                }
+            }
+            MappingTarget::Axis(axis, AxisRange::Positive) => self.set_axis(id, axis, magnitude, f),
+            MappingTarget::Axis(axis, AxisRange::Negative) => {
+                self.set_axis(id, axis, -magnitude, f)
+            }
+        }
+    }
+}
+
+fn hat_mask(state: HatState) -> u8 {
+    match state {
+        HatState::Centered => 0,
+        HatState::Up => 1,
+        HatState::Right => 2,
+        HatState::Down => 4,
+        HatState::Left => 8,
+        HatState::RightUp => 3,
+        HatState::RightDown => 6,
+        HatState::LeftUp => 9,
+        HatState::LeftDown => 12,
+    }
+}
+
+/// Converts raw controller events to standard gamepad events.
+///
+/// A controller must be connected with its GUID before its events are converted.
+/// This happens automatically on controller connection events that have a GUID.
+/// Events of controllers without a mapping are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct Gamepads {
+    /// The mappings used for connected controllers.
+    pub database: MappingDatabase,
+    gamepads: HashMap<u32, Gamepad>,
+}
+
+impl Gamepads {
+    /// Creates a new gamepad helper using a mapping database.
+    pub fn new(database: MappingDatabase) -> Gamepads {
+        Gamepads {
+            database,
+            gamepads: HashMap::new(),
+        }
+    }
+
+    /// Connects a controller id with a GUID.
+    ///
+    /// Returns `false` if there is no mapping for the GUID.
+    pub fn connect(&mut self, id: u32, guid: &str) -> bool {
+        match self.database.get(guid) {
+            Some(mapping) => {
+                self.gamepads.insert(
+                    id,
+                    Gamepad {
+                        mapping: mapping.clone(),
+                        buttons: vec![],
+                        axes: HashMap::new(),
+                        hats: HashMap::new(),
+                    },
+                );
+                true
+            }
+            None => {
+                self.gamepads.remove(&id);
+                false
+            }
+        }
+    }
+
+    /// Disconnects a controller.
+    pub fn disconnect(&mut self, id: u32) {
+        self.gamepads.remove(&id);
+    }
+
+    /// Disconnects a controller, calling the closure with releases of pressed buttons.
+    fn remove<F>(&mut self, id: u32, f: &mut F)
+    where
+        F: FnMut(GamepadEvent),
+    {
+        if let Some(gamepad) = self.gamepads.remove(&id) {
+            for &button in &gamepad.buttons {
+                f(GamepadEvent::Button {
+                    id,
+                    button,
+                    state: ButtonState::Release,
+                });
+            }
+        }
+    }
+
+    /// Gets the name of a connected controller from its mapping.
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.gamepads.get(&id).map(|g| &*g.mapping.name)
+    }
+
+    /// Returns `true` if a gamepad button is pressed.
+    pub fn button(&self, id: u32, button: GamepadButton) -> bool {
+        self.gamepads
+            .get(&id)
+            .map(|g| g.buttons.contains(&button))
+            .unwrap_or(false)
+    }
+
+    /// Returns the normalized position of a gamepad axis.
+    pub fn axis(&self, id: u32, axis: GamepadAxis) -> f64 {
+        self.gamepads
+            .get(&id)
+            .and_then(|g| g.axes.get(&axis).cloned())
+            .unwrap_or(0.0)
+    }
+
+    /// Handles a raw controller event, calling the closure with gamepad events.
+    ///
+    /// When a controller is removed, its pressed buttons are released.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(GamepadEvent),
+    {
+        if let Some(args) = e.controller_connection_args() {
+            self.remove(args.id, &mut f);
+            if let (ConnectionState::Added, Some(guid)) = (args.state, &args.guid) {
+                self.connect(args.id, guid);
+            }
+        }
+        if let Some(args) = e.button_args() {
+            match args.button {
+                Button::Controller(ControllerButton { id, button }) => {
+                    let gamepad = match self.gamepads.get_mut(&id) {
+                        Some(x) => x,
+                        None => return,
+                    };
+                    let value = match args.state {
+                        ButtonState::Press => 1.0,
+                        ButtonState::Release => 0.0,
+                    };
+                    for i in 0..gamepad.mapping.bindings.len() {
+                        let (source, target) = gamepad.mapping.bindings[i];
+                        if source == MappingSource::Button(button) {
+                            gamepad.apply(id, false, value, target, &mut f);
+                        }
+                    }
+                }
+                Button::Hat(hat) => {
+                    let gamepad = match self.gamepads.get_mut(&hat.id) {
+                        Some(x) => x,
+                        None => return,
+                    };
+                    let new_mask = hat_mask(hat.state);
+                    let old_mask = gamepad.hats.insert(hat.which, new_mask).unwrap_or(0);
+                    for i in 0..gamepad.mapping.bindings.len() {
+                        let (source, target) = gamepad.mapping.bindings[i];
+                        if let MappingSource::Hat { hat: which, mask } = source {
+                            if which == hat.which && (old_mask ^ new_mask) & mask != 0 {
+                                let value = if new_mask & mask != 0 { 1.0 } else { 0.0 };
+                                gamepad.apply(hat.id, false, value, target, &mut f);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(args) = e.controller_axis_args() {
+            let gamepad = match self.gamepads.get_mut(&args.id) {
+                Some(x) => x,
+                None => return,
+            };
+            for i in 0..gamepad.mapping.bindings.len() {
+                let (source, target) = gamepad.mapping.bindings[i];
+                if let MappingSource::Axis {
+                    axis,
+                    range,
+                    invert,
+                } = source
+                {
+                    if axis != args.axis {
+                        continue;
+                    }
+                    let value = if invert {
+                        -args.position
+                    } else {
+                        args.position
+                    };
+                    match range {
+                        AxisRange::Full => gamepad.apply(args.id, true, value, target, &mut f),
+                        AxisRange::Positive => {
+                            gamepad.apply(args.id, false, value.max(0.0), target, &mut f)
+                        }
+                        AxisRange::Negative => {
+                            gamepad.apply(args.id, false, (-value).max(0.0), target, &mut f)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ButtonArgs, ControllerAxisArgs, ControllerHat, Event};
+
+    const XBOX: &str = "030000005e0400008e02000014010000,X360 Controller,a:b0,b:b1,\
+        back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b8,leftshoulder:b4,\
+        leftstick:b9,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b10,\
+        righttrigger:a5,rightx:a3,righty:a4~,start:b7,x:b2,y:b3,platform:Linux,";
+
+    #[test]
+    fn test_parse_mapping() {
+        let mapping: Mapping = XBOX.parse().unwrap();
+        assert_eq!(mapping.name, "X360 Controller");
+        assert_eq!(mapping.platform.as_deref(), Some("Linux"));
+        assert_eq!(mapping.bindings.len(), 21);
+        assert!(mapping.bindings.contains(&(
+            MappingSource::Hat { hat: 0, mask: 4 },
+            MappingTarget::Button(GamepadButton::DPadDown)
+        )));
+        assert!(mapping.bindings.contains(&(
+            MappingSource::Axis {
+                axis: 4,
+                range: AxisRange::Full,
+                invert: true
+            },
+            MappingTarget::Axis(GamepadAxis::RightY, AxisRange::Full)
+        )));
+        assert_eq!(
+            "guid,name,a:q0".parse::<Mapping>(),
+            Err(MappingError::InvalidBinding("a:q0".into()))
+        );
+        assert_eq!(",name".parse::<Mapping>(), Err(MappingError::MissingGuid));
+    }
+
+    #[test]
+    fn test_gamepads() {
+        let mut db = MappingDatabase::new();
+        db.platform = Some("Windows".into());
+        assert_eq!(db.add_mappings(&format!("# comment\n\n{}", XBOX)), Ok(0));
+        db.platform = Some("Linux".into());
+        assert_eq!(db.add_mappings(XBOX), Ok(1));
+
+        let mut gamepads = Gamepads::new(db);
+        assert!(!gamepads.connect(1, "unknown"));
+        assert!(gamepads.connect(0, "030000005E0400008E02000014010000"));
+        assert_eq!(gamepads.name(0), Some("X360 Controller"));
+
+        let mut out = vec![];
+        let events: Vec<Event> = vec![
+            ButtonArgs::new(ButtonState::Press, ControllerButton::new(0, 0).into(), None).into(),
+            ButtonArgs::new(
+                ButtonState::Press,
+                Button::Hat(ControllerHat::new(0, 0, HatState::RightDown)),
+                None,
+            )
+            .into(),
+            ControllerAxisArgs::new(0, 2, 0.0).into(),
+            ControllerAxisArgs::new(0, 4, 0.5).into(),
+            ControllerAxisArgs::new(1, 0, 0.5).into(),
+        ];
+        for e in &events {
+            gamepads.event(e, |g| out.push(g));
+        }
+        assert_eq!(
+            out,
+            vec![
+                GamepadEvent::Button {
+                    id: 0,
+                    button: GamepadButton::South,
+                    state: ButtonState::Press
+                },
+                GamepadEvent::Button {
+                    id: 0,
+                    button: GamepadButton::DPadDown,
+                    state: ButtonState::Press
+                },
+                GamepadEvent::Button {
+                    id: 0,
+                    button: GamepadButton::DPadRight,
+                    state: ButtonState::Press
+                },
+                GamepadEvent::Axis {
+                    id: 0,
+                    axis: GamepadAxis::LeftTrigger,
+                    position: 0.5
+                },
+                GamepadEvent::Axis {
+                    id: 0,
+                    axis: GamepadAxis::RightY,
+                    position: -0.5
+                },
+            ]
+        );
+        assert!(gamepads.button(0, GamepadButton::DPadRight));
+        assert_eq!(gamepads.axis(0, GamepadAxis::RightY), -0.5);
+    }
+
+    #[test]
+    fn test_gamepads_connection() {
+        use crate::ControllerConnectionArgs;
+
+        let mut db = MappingDatabase::new();
+        db.add_mapping(XBOX).unwrap();
+        let mut gamepads = Gamepads::new(db);
+        let guid = "030000005e0400008e02000014010000";
+
+        let mut out = vec![];
+        let events: Vec<Event> = vec![
+            ControllerConnectionArgs::new(
+                3,
+                ConnectionState::Added,
+                "Pad".into(),
+                Some(guid.into()),
+            )
+            .into(),
+            ButtonArgs::new(ButtonState::Press, ControllerButton::new(3, 7).into(), None).into(),
+            ControllerConnectionArgs::new(3, ConnectionState::Removed, "Pad".into(), None).into(),
+            ButtonArgs::new(ButtonState::Press, ControllerButton::new(3, 7).into(), None).into(),
+        ];
+        for e in &events {
+            gamepads.event(e, |g| out.push(g));
+        }
+        let button = |state| GamepadEvent::Button {
+            id: 3,
+            button: GamepadButton::Start,
+            state,
+        };
+        assert_eq!(
+            out,
+            vec![button(ButtonState::Press), button(ButtonState::Release)]
+        );
+        assert_eq!(gamepads.name(3), None);
+    }
+}
@@ -3,10 +3,11 @@
 use std::any::Any;
 
 use crate::{
-    AfterRenderEvent, ButtonEvent, CloseEvent, ControllerAxisEvent, CursorEvent, Event, EventId,
-    FocusEvent, IdleEvent, Input, Loop, Motion, MouseCursorEvent, MouseRelativeEvent,
-    MouseScrollEvent, PressEvent, ReleaseEvent, RenderEvent, ResizeEvent, TextEvent, TimeStamp,
-    TouchEvent, UpdateEvent,
+    AfterRenderEvent, ButtonEvent, CloseEvent, ControllerAxisEvent, ControllerConnectionEvent,
+    CursorEvent, CustomEvent, Event, EventId, FocusEvent, GestureEvent, IdleEvent, ImeEvent, Input,
+    Loop, MaximizedEvent, MinimizedEvent, Motion, MouseCursorEvent, MouseRelativeEvent,
+    MouseScrollEvent, MovedEvent, OccludedEvent, PenEvent, PressEvent, ReleaseEvent, RenderEvent,
+    ResizeEvent, ScaleFactorEvent, ScrollEvent, TextEvent, TimeStamp, TouchEvent, UpdateEvent,
 };
 
 /// Implemented by all events.
@@ -24,17 +25,28 @@ pub trait GenericEvent:
     + AfterRenderEvent
     + CloseEvent
     + ControllerAxisEvent
+    + ControllerConnectionEvent
     + CursorEvent
+    + CustomEvent
     + FocusEvent
+    + GestureEvent
     + IdleEvent
+    + ImeEvent
+    + MaximizedEvent
+    + MinimizedEvent
     + MouseCursorEvent
     + MouseRelativeEvent
     + MouseScrollEvent
+    + MovedEvent
+    + OccludedEvent
+    + PenEvent
     + ButtonEvent
     + PressEvent
     + ReleaseEvent
     + RenderEvent
     + ResizeEvent
+    + ScaleFactorEvent
+    + ScrollEvent
     + TextEvent
     + TouchEvent
     + UpdateEvent
@@ -46,6 +58,9 @@ pub trait GenericEvent:
     /// The id of this event.
     fn event_id(&self) -> EventId;
     /// Calls closure with arguments
+    ///
+    /// For custom events, the closure is called with the `Arc` storing the arguments.
+    /// Use [`CustomEvent::custom`] to get typed arguments instead.
     fn with_args<F, U>(&'_ self, f: F) -> U
     where
         F: FnMut(&dyn Any) -> U;
@@ -55,27 +70,101 @@ pub trait GenericEvent:
     fn time_stamp(&self) -> Option<TimeStamp>;
 }
 
-impl GenericEvent for Event {
-    fn event_id(&self) -> EventId {
+impl Input {
+    /// Returns the id of the event with this input.
+    pub fn event_id(&self) -> EventId {
         use crate::event_id::*;
 
         match *self {
-            Event::Input(Input::Cursor(_), _) => CURSOR,
-            Event::Input(Input::Focus(_), _) => FOCUS,
-            Event::Input(Input::Close(_), _) => CLOSE,
-            Event::Input(Input::Move(Motion::MouseCursor(_)), _) => MOUSE_CURSOR,
-            Event::Input(Input::Move(Motion::MouseRelative(_)), _) => MOUSE_RELATIVE,
-            Event::Input(Input::Move(Motion::MouseScroll(_)), _) => MOUSE_SCROLL,
-            Event::Input(Input::Move(Motion::ControllerAxis(_)), _) => CONTROLLER_AXIS,
-            Event::Input(Input::Move(Motion::Touch(_)), _) => TOUCH,
-            Event::Input(Input::Button(_), _) => BUTTON,
-            Event::Input(Input::Resize(_), _) => RESIZE,
-            Event::Input(Input::Text(_), _) => TEXT,
-            Event::Input(Input::FileDrag(_), _) => FILE_DRAG,
-            Event::Loop(Loop::Update(_)) => UPDATE,
-            Event::Loop(Loop::Render(_)) => RENDER,
-            Event::Loop(Loop::AfterRender(_)) => AFTER_RENDER,
-            Event::Loop(Loop::Idle(_)) => IDLE,
+            Input::Cursor(_) => CURSOR,
+            Input::Focus(_) => FOCUS,
+            Input::Close(_) => CLOSE,
+            Input::Moved(_) => MOVED,
+            Input::Minimized(_) => MINIMIZED,
+            Input::Maximized(_) => MAXIMIZED,
+            Input::Occluded(_) => OCCLUDED,
+            Input::Controller(_) => CONTROLLER_CONNECTION,
+            Input::Move(Motion::MouseCursor(_)) => MOUSE_CURSOR,
+            Input::Move(Motion::MouseRelative(_)) => MOUSE_RELATIVE,
+            Input::Move(Motion::MouseScroll(_)) => MOUSE_SCROLL,
+            Input::Move(Motion::Scroll(_)) => SCROLL,
+            Input::Move(Motion::ControllerAxis(_)) => CONTROLLER_AXIS,
+            Input::Move(Motion::Touch(_)) => TOUCH,
+            Input::Move(Motion::Pen(_)) => PEN,
+            Input::Button(_) => BUTTON,
+            Input::Resize(_) => RESIZE,
+            Input::ScaleFactor(_) => SCALE_FACTOR,
+            Input::Text(_) => TEXT,
+            Input::Ime(_) => IME,
+            Input::FileDrag(_) => FILE_DRAG,
+            Input::Gesture(_) => GESTURE,
+        }
+    }
+
+    /// Calls closure with the arguments of this input.
+    pub fn with_args<F, U>(&self, mut f: F) -> U
+    where
+        F: FnMut(&dyn Any) -> U,
+    {
+        match *self {
+            Input::Cursor(cursor) => f(&cursor as &dyn Any),
+            Input::Focus(focused) => f(&focused as &dyn Any),
+            Input::Close(ref args) => f(args as &dyn Any),
+            Input::Moved(pos) => f(&pos as &dyn Any),
+            Input::Minimized(minimized) => f(&minimized as &dyn Any),
+            Input::Maximized(maximized) => f(&maximized as &dyn Any),
+            Input::Occluded(occluded) => f(&occluded as &dyn Any),
+            Input::Controller(ref args) => f(args as &dyn Any),
+            Input::Move(Motion::ControllerAxis(args)) => f(&args as &dyn Any),
+            Input::Move(Motion::MouseCursor(pos)) => f(&pos as &dyn Any),
+            Input::Move(Motion::MouseRelative(pos)) => f(&pos as &dyn Any),
+            Input::Move(Motion::MouseScroll(pos)) => f(&pos as &dyn Any),
+            Input::Move(Motion::Scroll(args)) => f(&args as &dyn Any),
+            Input::Move(Motion::Touch(args)) => f(&args as &dyn Any),
+            Input::Move(Motion::Pen(args)) => f(&args as &dyn Any),
+            Input::Button(ref args) => f(args as &dyn Any),
+            Input::Resize(ref args) => f(args as &dyn Any),
+            Input::ScaleFactor(ref args) => f(args as &dyn Any),
+            Input::Text(ref text) => f(text as &dyn Any),
+            Input::Ime(ref ime) => f(ime as &dyn Any),
+            Input::FileDrag(ref file_drag) => f(file_drag as &dyn Any),
+            Input::Gesture(ref args) => f(args as &dyn Any),
+        }
+    }
+}
+
+impl Loop {
+    /// Returns the id of the event with this loop event.
+    pub fn event_id(&self) -> EventId {
+        use crate::event_id::*;
+
+        match *self {
+            Loop::Update(_) => UPDATE,
+            Loop::Render(_) => RENDER,
+            Loop::AfterRender(_) => AFTER_RENDER,
+            Loop::Idle(_) => IDLE,
+        }
+    }
+
+    /// Calls closure with the arguments of this loop event.
+    pub fn with_args<F, U>(&self, mut f: F) -> U
+    where
+        F: FnMut(&dyn Any) -> U,
+    {
+        match *self {
+            Loop::Update(ref args) => f(args as &dyn Any),
+            Loop::Render(ref args) => f(args as &dyn Any),
+            Loop::AfterRender(ref args) => f(args as &dyn Any),
+            Loop::Idle(ref args) => f(args as &dyn Any),
+        }
+    }
+}
+
+impl GenericEvent for Event {
+    fn event_id(&self) -> EventId {
+        match *self {
+            Event::Input(ref input, _) => input.event_id(),
+            Event::Loop(ref args) => args.event_id(),
             Event::Custom(event_id, _, _) => event_id,
         }
     }
@@ -85,22 +174,8 @@ impl GenericEvent for Event {
         F: FnMut(&dyn Any) -> U,
     {
         match *self {
-            Event::Input(Input::Cursor(cursor), _) => f(&cursor as &dyn Any),
-            Event::Input(Input::Focus(focused), _) => f(&focused as &dyn Any),
-            Event::Input(Input::Close(ref args), _) => f(args as &dyn Any),
-            Event::Input(Input::Move(Motion::ControllerAxis(args)), _) => f(&args as &dyn Any),
-            Event::Input(Input::Move(Motion::MouseCursor(pos)), _) => f(&pos as &dyn Any),
-            Event::Input(Input::Move(Motion::MouseRelative(pos)), _) => f(&pos as &dyn Any),
-            Event::Input(Input::Move(Motion::MouseScroll(pos)), _) => f(&pos as &dyn Any),
-            Event::Input(Input::Move(Motion::Touch(args)), _) => f(&args as &dyn Any),
-            Event::Input(Input::Button(ref args), _) => f(args as &dyn Any),
-            Event::Input(Input::Resize(ref args), _) => f(args as &dyn Any),
-            Event::Input(Input::Text(ref text), _) => f(text as &dyn Any),
-            Event::Input(Input::FileDrag(ref file_drag), _) => f(file_drag as &dyn Any),
-            Event::Loop(Loop::Update(ref args)) => f(args as &dyn Any),
-            Event::Loop(Loop::Render(ref args)) => f(args as &dyn Any),
-            Event::Loop(Loop::AfterRender(ref args)) => f(args as &dyn Any),
-            Event::Loop(Loop::Idle(ref args)) => f(args as &dyn Any),
+            Event::Input(ref input, _) => input.with_args(f),
+            Event::Loop(ref args) => args.with_args(f),
             Event::Custom(_, ref args, _) => f(args),
         }
     }
@@ -0,0 +1,42 @@
+use crate::Input;
+
+/// Models text composition with an input method editor (IME).
+///
+/// Input methods are used to type text that is composed from several key presses,
+/// for example Chinese, Japanese and Korean.
+/// While composing, the uncommitted text (preedit) should be shown to the user,
+/// but not inserted into the document until it is committed.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, Hash)]
+pub enum Ime {
+    /// Composition started.
+    Start,
+    /// The uncommitted text changed.
+    Preedit {
+        /// The uncommitted text.
+        text: String,
+        /// The cursor or selection range as byte offsets into the text.
+        ///
+        /// When start and end are equal, this is the cursor position.
+        /// Set to `None` when the cursor should be hidden.
+        cursor: Option<[usize; 2]>,
+    },
+    /// Text was committed, ending the composition.
+    Commit(String),
+    /// Composition ended without committing text.
+    End,
+}
+
+crate::event_trait! {
+    /// When composing text with an input method editor (IME).
+    pub trait ImeEvent {
+        args: &Ime,
+        from_args: from_ime,
+        accessor: ime,
+        args_getter: ime_args,
+        event: Input(Input::Ime),
+        test: test_input_ime(Ime::Preedit {
+            text: "にほ".into(),
+            cursor: Some([6, 6]),
+        }),
+    }
+}
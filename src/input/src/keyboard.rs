@@ -2,7 +2,7 @@
 
 use std::default::Default;
 
-use crate::{Button, GenericEvent};
+use crate::{Button, ButtonArgs, ButtonEvent, ButtonState, GenericEvent, TextEvent};
 
 // Defining every combination to allow assignment in static expressions.
 bitflags!(
@@ -61,6 +61,7 @@ impl ModifierKey {
     /// Change modifier key state depending on input.
     ///
     /// If the left or side button is released, it counts as a release.
+    /// Use [`ModifierState`] to track left and right side separately.
     pub fn event<E: GenericEvent>(&mut self, e: &E) {
         if let Some(button) = e.press_args() {
             match button {
@@ -92,6 +93,305 @@ impl Default for ModifierKey {
     }
 }
 
+bitflags!(
+    /// Modifier keys on the left and right side of the keyboard.
+    #[derive(Default, Deserialize, Serialize)]
+    pub struct ModifierSide: u8 {
+        /// Left Ctrl.
+        const L_CTRL    = 0b0000_0001;
+        /// Right Ctrl.
+        const R_CTRL    = 0b0000_0010;
+        /// Left Shift.
+        const L_SHIFT   = 0b0000_0100;
+        /// Right Shift.
+        const R_SHIFT   = 0b0000_1000;
+        /// Left Alt.
+        const L_ALT     = 0b0001_0000;
+        /// Right Alt.
+        const R_ALT     = 0b0010_0000;
+        /// Left Gui.
+        const L_GUI     = 0b0100_0000;
+        /// Right Gui.
+        const R_GUI     = 0b1000_0000;
+    }
+);
+
+bitflags!(
+    /// Toggle state of lock keys.
+    #[derive(Default, Deserialize, Serialize)]
+    pub struct LockKey: u8 {
+        /// Caps Lock is on.
+        const CAPS_LOCK     = 0b0000_0001;
+        /// Num Lock is on.
+        const NUM_LOCK      = 0b0000_0010;
+        /// Scroll Lock is on.
+        const SCROLL_LOCK   = 0b0000_0100;
+    }
+);
+
+/// Stores the state of modifier keys, keeping left and right side separate,
+/// and the toggle state of lock keys.
+///
+/// A snapshot can be attached to button events,
+/// such that handlers do not need to track modifier keys themselves.
+#[derive(
+    Copy, Clone, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Debug,
+)]
+pub struct ModifierState {
+    /// The modifier keys that are held down.
+    pub sides: ModifierSide,
+    /// The lock keys that are toggled on.
+    pub locks: LockKey,
+}
+
+impl ModifierState {
+    /// Creates a new state with no modifiers and no locks.
+    pub fn new() -> ModifierState {
+        ModifierState::default()
+    }
+
+    /// Change modifier state depending on input.
+    ///
+    /// Pressing a lock key toggles its state, ignoring repeated presses.
+    /// When a button event has a snapshot attached by the window backend,
+    /// the state is replaced by the snapshot.
+    /// Modifiers are released when the window loses focus, but locks are kept.
+    pub fn event<E: GenericEvent>(&mut self, e: &E) {
+        if let Some(args) = e.button_args() {
+            if let Some(snapshot) = args.modifiers {
+                *self = snapshot;
+            } else if let Button::Keyboard(key) = args.button {
+                let side = match key {
+                    Key::LCtrl => ModifierSide::L_CTRL,
+                    Key::RCtrl => ModifierSide::R_CTRL,
+                    Key::LShift => ModifierSide::L_SHIFT,
+                    Key::RShift => ModifierSide::R_SHIFT,
+                    Key::LAlt => ModifierSide::L_ALT,
+                    Key::RAlt => ModifierSide::R_ALT,
+                    Key::LGui => ModifierSide::L_GUI,
+                    Key::RGui => ModifierSide::R_GUI,
+                    _ => ModifierSide::empty(),
+                };
+                let lock = match key {
+                    Key::CapsLock => LockKey::CAPS_LOCK,
+                    Key::NumLockClear => LockKey::NUM_LOCK,
+                    Key::ScrollLock => LockKey::SCROLL_LOCK,
+                    _ => LockKey::empty(),
+                };
+                match args.state {
+                    ButtonState::Press => {
+                        self.sides.insert(side);
+                        if !args.repeat {
+                            self.locks.toggle(lock);
+                        }
+                    }
+                    ButtonState::Release => self.sides.remove(side),
+                }
+            }
+        }
+        if let Some(false) = e.focus_args() {
+            self.sides = ModifierSide::empty();
+        }
+    }
+
+    /// Returns the modifier keys, combining left and right side.
+    ///
+    /// A modifier is active as long as either side is held down.
+    pub fn modifier_key(&self) -> ModifierKey {
+        let pairs = [
+            (
+                ModifierSide::L_CTRL | ModifierSide::R_CTRL,
+                ModifierKey::CTRL,
+            ),
+            (
+                ModifierSide::L_SHIFT | ModifierSide::R_SHIFT,
+                ModifierKey::SHIFT,
+            ),
+            (ModifierSide::L_ALT | ModifierSide::R_ALT, ModifierKey::ALT),
+            (ModifierSide::L_GUI | ModifierSide::R_GUI, ModifierKey::GUI),
+        ];
+        let mut res = ModifierKey::NO_MODIFIER;
+        for &(sides, modifier) in &pairs {
+            if self.sides.intersects(sides) {
+                res.insert(modifier);
+            }
+        }
+        res
+    }
+
+    /// Attaches a snapshot of this state to a button event.
+    ///
+    /// Call this after [`ModifierState::event`] to include the change made by the event.
+    /// Returns `None` if this is not a button event.
+    pub fn attach<E: GenericEvent>(&self, e: &E) -> Option<E> {
+        let args = e.button_args()?;
+        ButtonEvent::from_button_args(
+            ButtonArgs {
+                modifiers: Some(*self),
+                ..args
+            },
+            e,
+        )
+    }
+}
+
+/// The default delay in seconds before a held key starts repeating.
+pub const DEFAULT_REPEAT_DELAY: f64 = 0.5;
+/// The default number of repeated presses per second.
+pub const DEFAULT_REPEAT_RATE: f64 = 25.0;
+
+/// Generates repeated key presses while a keyboard key is held down.
+///
+/// Window backends differ in whether they send repeated presses,
+/// and with which delay and rate.
+/// When enabled, repeated presses from the window backend are suppressed,
+/// together with the text they produce.
+/// New presses marked as repeated are synthesized on update events,
+/// followed by the text received after the original press, if any.
+/// Modifier keys do not repeat.
+///
+/// When disabled, all events are passed through unchanged.
+#[derive(Clone, Debug)]
+pub struct KeyRepeat {
+    /// Whether to synthesize repeated presses.
+    pub enabled: bool,
+    /// The delay in seconds before the first repeated press.
+    pub delay: f64,
+    /// The number of repeated presses per second.
+    pub rate: f64,
+    held: Option<HeldKey>,
+    suppress_text: bool,
+}
+
+/// Stores the key that is currently repeating.
+#[derive(Clone, Debug)]
+struct HeldKey {
+    args: ButtonArgs,
+    text: Option<String>,
+    /// Whether text events belong to the original press.
+    accepts_text: bool,
+    /// Seconds until next repeated press.
+    wait: f64,
+}
+
+impl KeyRepeat {
+    /// Creates a new enabled key repeat with default delay and rate.
+    pub fn new() -> KeyRepeat {
+        KeyRepeat {
+            enabled: true,
+            delay: DEFAULT_REPEAT_DELAY,
+            rate: DEFAULT_REPEAT_RATE,
+            held: None,
+            suppress_text: false,
+        }
+    }
+
+    /// Handles an event, calling the closure with the resulting events.
+    ///
+    /// Repeated presses are emitted right before the update event that triggered them.
+    /// Since timing depends on update events, no presses are repeated
+    /// when the event loop does not emit update events.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(&E),
+    {
+        if !self.enabled {
+            self.held = None;
+            f(e);
+            return;
+        }
+
+        if let Some(args) = e.button_args() {
+            if let Some(held) = &mut self.held {
+                held.accepts_text = false;
+            }
+            self.suppress_text = false;
+            if let Button::Keyboard(key) = args.button {
+                match args.state {
+                    ButtonState::Press if args.repeat => {
+                        // Replaced by synthesized presses.
+                        self.suppress_text = true;
+                        return;
+                    }
+                    ButtonState::Press => {
+                        if !key.is_modifier() {
+                            self.held = Some(HeldKey {
+                                args,
+                                text: None,
+                                accepts_text: true,
+                                wait: self.delay,
+                            });
+                        }
+                    }
+                    ButtonState::Release => {
+                        if let Some(HeldKey {
+                            args:
+                                ButtonArgs {
+                                    button: Button::Keyboard(held_key),
+                                    ..
+                                },
+                            ..
+                        }) = self.held
+                        {
+                            if held_key == key {
+                                self.held = None;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(text) = e.text_args() {
+            if self.suppress_text {
+                self.suppress_text = false;
+                return;
+            }
+            if let Some(held) = &mut self.held {
+                if held.accepts_text {
+                    held.text.get_or_insert_with(String::new).push_str(&text);
+                }
+            }
+        }
+
+        if let Some(false) = e.focus_args() {
+            self.held = None;
+        }
+
+        if let Some(args) = e.update_args() {
+            if let Some(held) = &mut self.held {
+                held.accepts_text = false;
+                held.wait -= args.dt;
+                while held.wait <= 0.0 {
+                    let repeat = ButtonArgs {
+                        repeat: true,
+                        ..held.args
+                    };
+                    if let Some(ev) = ButtonEvent::from_button_args(repeat, e) {
+                        f(&ev);
+                    }
+                    if let Some(text) = &held.text {
+                        if let Some(ev) = TextEvent::from_text(text, e) {
+                            f(&ev);
+                        }
+                    }
+                    // A rate of zero or less stops repeating.
+                    held.wait += 1.0 / self.rate.max(0.0);
+                }
+            }
+        }
+
+        f(e);
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> KeyRepeat {
+        KeyRepeat::new()
+    }
+}
+
 /// Represent a keyboard key.
 /// Keycodes follows SDL <http://wiki.libsdl.org/SDLKeycodeLookup>
 #[allow(missing_docs)]
@@ -586,6 +886,21 @@ impl Key {
     pub fn code(&self) -> i32 {
         *self as i32
     }
+
+    /// Returns `true` if this is a Ctrl, Shift, Alt or Gui key.
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            *self,
+            Key::LCtrl
+                | Key::RCtrl
+                | Key::LShift
+                | Key::RShift
+                | Key::LAlt
+                | Key::RAlt
+                | Key::LGui
+                | Key::RGui
+        )
+    }
 }
 
 impl From<Key> for u32 {
@@ -597,6 +912,107 @@ impl From<Key> for u32 {
 
 #[cfg(test)]
 mod tests {
+    use crate::{ButtonArgs, ButtonEvent, ButtonState, Event, Input, UpdateArgs};
+
+    fn press(key: super::Key, repeat: bool) -> Event {
+        ButtonArgs {
+            state: ButtonState::Press,
+            button: key.into(),
+            scancode: None,
+            repeat,
+            modifiers: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn key_repeat() {
+        use super::{Key, KeyRepeat};
+
+        let mut repeat = KeyRepeat::new();
+        repeat.delay = 0.5;
+        repeat.rate = 4.0;
+        let mut out = vec![];
+        let events = vec![
+            press(Key::A, false),
+            Input::Text("a".into()).into(),
+            UpdateArgs { dt: 0.25 }.into(),
+            press(Key::A, true),
+            Input::Text("a".into()).into(),
+            UpdateArgs { dt: 0.25 }.into(),
+            UpdateArgs { dt: 0.5 }.into(),
+            ButtonArgs::new(ButtonState::Release, Key::A.into(), None).into(),
+            UpdateArgs { dt: 1.0 }.into(),
+        ];
+        for e in &events {
+            repeat.event(e, |e: &Event| out.push(e.clone()));
+        }
+        let expected: Vec<Event> = vec![
+            press(Key::A, false),
+            Input::Text("a".into()).into(),
+            UpdateArgs { dt: 0.25 }.into(),
+            press(Key::A, true),
+            Input::Text("a".into()).into(),
+            UpdateArgs { dt: 0.25 }.into(),
+            press(Key::A, true),
+            Input::Text("a".into()).into(),
+            press(Key::A, true),
+            Input::Text("a".into()).into(),
+            UpdateArgs { dt: 0.5 }.into(),
+            ButtonArgs::new(ButtonState::Release, Key::A.into(), None).into(),
+            UpdateArgs { dt: 1.0 }.into(),
+        ];
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn key_repeat_disabled() {
+        use super::{Key, KeyRepeat};
+
+        let mut repeat = KeyRepeat::new();
+        repeat.enabled = false;
+        let mut out = vec![];
+        let events = vec![
+            press(Key::A, false),
+            UpdateArgs { dt: 1.0 }.into(),
+            press(Key::A, true),
+        ];
+        for e in &events {
+            repeat.event(e, |e: &Event| out.push(e.clone()));
+        }
+        assert_eq!(out, events);
+    }
+
+    #[test]
+    fn modifier_state() {
+        use super::{Key, LockKey, ModifierKey, ModifierSide, ModifierState};
+
+        let mut state = ModifierState::new();
+        let release =
+            |key: Key| -> Event { ButtonArgs::new(ButtonState::Release, key.into(), None).into() };
+        state.event(&press(Key::LCtrl, false));
+        state.event(&press(Key::RCtrl, false));
+        state.event(&release(Key::LCtrl));
+        assert_eq!(state.sides, ModifierSide::R_CTRL);
+        assert_eq!(state.modifier_key(), ModifierKey::CTRL);
+        state.event(&release(Key::RCtrl));
+        assert_eq!(state.modifier_key(), ModifierKey::NO_MODIFIER);
+
+        state.event(&press(Key::CapsLock, false));
+        state.event(&press(Key::CapsLock, true));
+        state.event(&release(Key::CapsLock));
+        assert_eq!(state.locks, LockKey::CAPS_LOCK);
+        state.event(&press(Key::CapsLock, false));
+        assert_eq!(state.locks, LockKey::empty());
+
+        state.event(&press(Key::LShift, false));
+        let e = state.attach(&press(Key::A, false)).unwrap();
+        assert_eq!(e.button_args().unwrap().modifiers, Some(state));
+
+        state.event(&Event::from(Input::Focus(false)));
+        assert_eq!(state.sides, ModifierSide::empty());
+    }
+
     #[test]
     fn keycode() {
         use super::{Key, Key::*};
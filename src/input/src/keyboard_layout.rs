@@ -0,0 +1,611 @@
+//! Keyboard layouts mapping physical keys to characters.
+//!
+//! Physical keys are identified by scancodes, following SDL (<https://wiki.libsdl.org/SDL_Scancode>),
+//! as stored in [`ButtonArgs::scancode`](crate::ButtonArgs::scancode).
+//! A [`Layout`] tells which key and characters a physical key produces,
+//! and which keystrokes type a character.
+//! This is useful for tests and on-screen keyboards, where no window backend emits text events.
+//!
+//! Some layouts have dead keys, which do not produce a character,
+//! but change the character produced by the next key, e.g. `^` followed by `e` types `ê`.
+//! Use [`DeadKeys`] to type characters with dead keys.
+
+use crate::{
+    keyboard::{LockKey, ModifierSide, ModifierState},
+    Key,
+};
+
+/// A modifier level of a keyboard layout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// No modifiers.
+    Base,
+    /// Shift held down.
+    Shift,
+    /// Alt Gr held down, reported as right Alt or Ctrl+Alt.
+    AltGr,
+}
+
+impl Level {
+    /// All levels, in order of preference when typing a character.
+    pub const ALL: [Level; 3] = [Level::Base, Level::Shift, Level::AltGr];
+
+    /// Returns the level of modifier keys, or `None` if no character is produced.
+    ///
+    /// Ctrl, left Alt and GUI are used for shortcuts, so these do not produce characters,
+    /// except Ctrl+Alt, which Windows reports for Alt Gr.
+    /// Right Alt is Alt Gr.
+    pub fn from_modifiers(modifiers: ModifierState) -> Option<Level> {
+        let sides = modifiers.sides;
+        let ctrl = sides.intersects(ModifierSide::L_CTRL | ModifierSide::R_CTRL);
+        let alt = sides.intersects(ModifierSide::L_ALT | ModifierSide::R_ALT);
+        let gui = sides.intersects(ModifierSide::L_GUI | ModifierSide::R_GUI);
+        let alt_gr = sides.contains(ModifierSide::R_ALT) || (ctrl && alt);
+        if gui || ((ctrl || alt) && !alt_gr) {
+            None
+        } else if alt_gr {
+            Some(Level::AltGr)
+        } else if sides.intersects(ModifierSide::L_SHIFT | ModifierSide::R_SHIFT) {
+            Some(Level::Shift)
+        } else {
+            Some(Level::Base)
+        }
+    }
+
+    /// Returns the modifier keys to hold down for this level.
+    ///
+    /// Shift is held with left Shift, and Alt Gr with right Alt.
+    pub fn modifiers(self) -> ModifierState {
+        let sides = match self {
+            Level::Base => ModifierSide::empty(),
+            Level::Shift => ModifierSide::L_SHIFT,
+            Level::AltGr => ModifierSide::R_ALT,
+        };
+        ModifierState {
+            sides,
+            ..ModifierState::new()
+        }
+    }
+}
+
+/// The result of pressing a key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    /// Types a character.
+    Char(char),
+    /// A dead key, changing the next character.
+    Dead(char),
+}
+
+/// A key press typing a character, together with the modifiers to hold down.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Keystroke {
+    /// The physical key.
+    pub scancode: i32,
+    /// The key reported for the physical key in the layout.
+    pub key: Key,
+    /// The modifier level.
+    pub level: Level,
+}
+
+/// Scancodes of the number row, starting with the key left of `1`.
+const DIGITS: &[i32] = &[53, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 45, 46];
+/// Scancodes of the top letter row on ANSI keyboards, ending with backslash.
+const TOP_ANSI: &[i32] = &[20, 26, 8, 21, 23, 28, 24, 12, 18, 19, 47, 48, 49];
+/// Scancodes of the top letter row on ISO keyboards.
+const TOP_ISO: &[i32] = &[20, 26, 8, 21, 23, 28, 24, 12, 18, 19, 47, 48];
+/// Scancodes of the home row on ANSI keyboards.
+const HOME_ANSI: &[i32] = &[4, 22, 7, 9, 10, 11, 13, 14, 15, 51, 52];
+/// Scancodes of the home row on ISO keyboards, ending with the key left of return.
+const HOME_ISO: &[i32] = &[4, 22, 7, 9, 10, 11, 13, 14, 15, 51, 52, 50];
+/// Scancodes of the bottom row on ANSI keyboards.
+const BOTTOM_ANSI: &[i32] = &[29, 27, 6, 25, 5, 17, 16, 54, 55, 56];
+/// Scancodes of the bottom row on ISO keyboards, starting with the key right of left shift.
+const BOTTOM_ISO: &[i32] = &[100, 29, 27, 6, 25, 5, 17, 16, 54, 55, 56];
+
+const SCANCODE_RETURN: i32 = 40;
+const SCANCODE_TAB: i32 = 43;
+const SCANCODE_SPACE: i32 = 44;
+
+/// A row of physical keys, with characters per level.
+///
+/// Stores the scancodes of the keys and a string per level in [`Level::ALL`],
+/// with one character per key.
+/// Spaces mark keys without a character at a level.
+pub type Row = (&'static [i32], [&'static str; 3]);
+
+/// A keyboard layout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    name: &'static str,
+    rows: &'static [Row],
+    dead_keys: &'static [(i32, Level)],
+}
+
+/// US QWERTY layout.
+pub const US: Layout = Layout {
+    name: "us",
+    rows: &[
+        (DIGITS, ["`1234567890-=", "~!@#$%^&*()_+", ""]),
+        (TOP_ANSI, ["qwertyuiop[]\\", "QWERTYUIOP{}|", ""]),
+        (HOME_ANSI, ["asdfghjkl;'", "ASDFGHJKL:\"", ""]),
+        (BOTTOM_ANSI, ["zxcvbnm,./", "ZXCVBNM<>?", ""]),
+    ],
+    dead_keys: &[],
+};
+
+/// UK QWERTY layout.
+pub const UK: Layout = Layout {
+    name: "uk",
+    rows: &[
+        (DIGITS, ["`1234567890-=", "¬!\"£$%^&*()_+", "¦   €        "]),
+        (TOP_ISO, ["qwertyuiop[]", "QWERTYUIOP{}", "  é   úíó   "]),
+        (HOME_ISO, ["asdfghjkl;'#", "ASDFGHJKL:@~", "á           "]),
+        (BOTTOM_ISO, ["\\zxcvbnm,./", "|ZXCVBNM<>?", ""]),
+    ],
+    dead_keys: &[],
+};
+
+/// German QWERTZ layout.
+pub const GERMAN: Layout = Layout {
+    name: "de",
+    rows: &[
+        (
+            DIGITS,
+            ["^1234567890ß´", "°!\"§$%&/()=?`", "  ²³   {[]}\\ "],
+        ),
+        (TOP_ISO, ["qwertzuiopü+", "QWERTZUIOPÜ*", "@ €        ~"]),
+        (HOME_ISO, ["asdfghjklöä#", "ASDFGHJKLÖÄ'", ""]),
+        (BOTTOM_ISO, ["<yxcvbnm,.-", ">YXCVBNM;:_", "|      µ   "]),
+    ],
+    dead_keys: &[(53, Level::Base), (46, Level::Base), (46, Level::Shift)],
+};
+
+/// French AZERTY layout.
+pub const FRENCH: Layout = Layout {
+    name: "fr",
+    rows: &[
+        (
+            DIGITS,
+            ["²&é\"'(-è_çà)=", " 1234567890°+", "  ~#{[|`\\^@]}"],
+        ),
+        (TOP_ISO, ["azertyuiop^$", "AZERTYUIOP¨£", "  €        ¤"]),
+        (HOME_ISO, ["qsdfghjklmù*", "QSDFGHJKLM%µ", ""]),
+        (BOTTOM_ISO, ["<wxcvbn,;:!", ">WXCVBN?./§", ""]),
+    ],
+    dead_keys: &[
+        (31, Level::AltGr),
+        (36, Level::AltGr),
+        (47, Level::Base),
+        (47, Level::Shift),
+    ],
+};
+
+/// All layouts.
+pub const LAYOUTS: &[Layout] = &[US, UK, GERMAN, FRENCH];
+
+/// Characters composed with dead keys, as pairs of base and composed characters.
+const COMPOSE: &[(char, &str)] = &[
+    ('^', "aâeêiîoôuûAÂEÊIÎOÔUÛ"),
+    ('´', "aáeéiíoóuúyýAÁEÉIÍOÓUÚYÝ"),
+    ('`', "aàeèiìoòuùAÀEÈIÌOÒUÙ"),
+    ('¨', "aäeëiïoöuüyÿAÄEËIÏOÖUÜ"),
+    ('~', "aãnñoõAÃNÑOÕ"),
+];
+
+/// Returns the pairs of base and composed characters of a dead key.
+fn compose_pairs(dead: char) -> Vec<(char, char)> {
+    let pairs = match COMPOSE.iter().find(|&&(d, _)| d == dead) {
+        Some(&(_, pairs)) => pairs,
+        None => return vec![],
+    };
+    let chars: Vec<char> = pairs.chars().collect();
+    chars.chunks(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Composes a character typed after a dead key.
+///
+/// Returns `None` if the dead key does not change the character.
+pub fn compose(dead: char, ch: char) -> Option<char> {
+    compose_pairs(dead)
+        .into_iter()
+        .find(|&(base, _)| base == ch)
+        .map(|(_, composed)| composed)
+}
+
+impl Layout {
+    /// Creates a layout from rows of keys and the keys at levels which are dead keys.
+    ///
+    /// Space, return and tab are the same in all layouts, so these are not listed in rows.
+    ///
+    /// ```ignore
+    /// const DVORAK: Layout = Layout::new(
+    ///     "dvorak",
+    ///     &[(&[20, 26, 8, 21, 23, 28, 24, 12, 18, 19], ["',.pyfgcrl", "\"<>PYFGCRL", ""])],
+    ///     &[],
+    /// );
+    /// ```
+    pub const fn new(
+        name: &'static str,
+        rows: &'static [Row],
+        dead_keys: &'static [(i32, Level)],
+    ) -> Layout {
+        Layout {
+            name,
+            rows,
+            dead_keys,
+        }
+    }
+
+    /// Returns the built-in layout with a name, e.g. `"de"`.
+    pub fn from_name(name: &str) -> Option<Layout> {
+        LAYOUTS.iter().find(|layout| layout.name == name).copied()
+    }
+
+    /// Returns the name of the layout.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the character at a level of a physical key, ignoring dead keys.
+    fn char_at(&self, scancode: i32, level: Level) -> Option<char> {
+        match scancode {
+            SCANCODE_SPACE => return Some(' '),
+            SCANCODE_RETURN => return Some('\n'),
+            SCANCODE_TAB => return Some('\t'),
+            _ => {}
+        }
+        let &(scancodes, ref levels) = self.rows.iter().find(|row| row.0.contains(&scancode))?;
+        let i = scancodes.iter().position(|&s| s == scancode)?;
+        let ch = levels[level as usize].chars().nth(i)?;
+        if ch == ' ' {
+            None
+        } else {
+            Some(ch)
+        }
+    }
+
+    /// Returns what a physical key produces at a level.
+    pub fn symbol(&self, scancode: i32, level: Level) -> Option<Symbol> {
+        let ch = self.char_at(scancode, level)?;
+        if self.dead_keys.contains(&(scancode, level)) {
+            Some(Symbol::Dead(ch))
+        } else {
+            Some(Symbol::Char(ch))
+        }
+    }
+
+    /// Returns `true` if a physical key types a lowercase letter, and the uppercase with Shift.
+    fn is_letter(&self, scancode: i32) -> bool {
+        match (
+            self.char_at(scancode, Level::Base),
+            self.char_at(scancode, Level::Shift),
+        ) {
+            (Some(base), Some(shift)) => base.is_lowercase() && base.to_uppercase().eq([shift]),
+            _ => false,
+        }
+    }
+
+    /// Returns what a physical key produces with modifier keys held down.
+    ///
+    /// Caps Lock swaps the base and Shift levels of letter keys.
+    pub fn symbol_with_modifiers(&self, scancode: i32, modifiers: ModifierState) -> Option<Symbol> {
+        let level = match Level::from_modifiers(modifiers)? {
+            Level::Base if self.caps_lock(scancode, modifiers) => Level::Shift,
+            Level::Shift if self.caps_lock(scancode, modifiers) => Level::Base,
+            level => level,
+        };
+        self.symbol(scancode, level)
+    }
+
+    fn caps_lock(&self, scancode: i32, modifiers: ModifierState) -> bool {
+        modifiers.locks.contains(LockKey::CAPS_LOCK) && self.is_letter(scancode)
+    }
+
+    /// Returns the key reported for a physical key.
+    ///
+    /// Letter keys are reported by their letter in the layout.
+    /// The number row is reported as digits, and other keys by their base character.
+    /// Returns `Key::Unknown` for keys without a corresponding `Key`.
+    pub fn key(&self, scancode: i32) -> Key {
+        match scancode {
+            30..=38 => return Key::from(u32::from(b'1') + (scancode - 30) as u32),
+            39 => return Key::D0,
+            SCANCODE_SPACE => return Key::Space,
+            SCANCODE_RETURN => return Key::Return,
+            SCANCODE_TAB => return Key::Tab,
+            _ => {}
+        }
+        match self.char_at(scancode, Level::Base) {
+            Some(ch) if ch.is_ascii() => Key::from(ch.to_ascii_lowercase() as u32),
+            _ => Key::Unknown,
+        }
+    }
+
+    /// Returns the physical key of a key in the layout.
+    pub fn scancode(&self, key: Key) -> Option<i32> {
+        if key == Key::Unknown {
+            return None;
+        }
+        self.scancodes().find(|&scancode| self.key(scancode) == key)
+    }
+
+    fn scancodes(&self) -> impl Iterator<Item = i32> + '_ {
+        self.rows
+            .iter()
+            .flat_map(|row| row.0.iter().copied())
+            .chain(vec![SCANCODE_SPACE, SCANCODE_RETURN, SCANCODE_TAB])
+    }
+
+    fn keystroke(&self, scancode: i32, level: Level) -> Keystroke {
+        Keystroke {
+            scancode,
+            key: self.key(scancode),
+            level,
+        }
+    }
+
+    /// Returns the keystroke typing a character without dead keys.
+    fn direct_keystroke(&self, ch: char) -> Option<Keystroke> {
+        Level::ALL.iter().find_map(|&level| {
+            self.scancodes()
+                .find(|&scancode| self.symbol(scancode, level) == Some(Symbol::Char(ch)))
+                .map(|scancode| self.keystroke(scancode, level))
+        })
+    }
+
+    /// Returns the keystrokes typing a character.
+    ///
+    /// Characters are typed with one keystroke when possible,
+    /// preferring fewer modifiers.
+    /// Otherwise, a dead key is followed by the base character,
+    /// or by space to type the character of the dead key itself.
+    pub fn keystrokes(&self, ch: char) -> Option<Vec<Keystroke>> {
+        if let Some(keystroke) = self.direct_keystroke(ch) {
+            return Some(vec![keystroke]);
+        }
+        for &(scancode, level) in self.dead_keys {
+            let dead = match self.char_at(scancode, level) {
+                Some(dead) => dead,
+                None => continue,
+            };
+            let base = if dead == ch {
+                Some(' ')
+            } else {
+                compose_pairs(dead)
+                    .into_iter()
+                    .find(|&(_, composed)| composed == ch)
+                    .map(|(base, _)| base)
+            };
+            if let Some(next) = base.and_then(|base| self.direct_keystroke(base)) {
+                return Some(vec![self.keystroke(scancode, level), next]);
+            }
+        }
+        None
+    }
+}
+
+/// Types characters from key presses, composing characters after dead keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DeadKeys {
+    /// The layout of the keyboard.
+    pub layout: Layout,
+    pending: Option<char>,
+}
+
+impl DeadKeys {
+    /// Creates a new state without pending dead key.
+    pub fn new(layout: Layout) -> DeadKeys {
+        DeadKeys {
+            layout,
+            pending: None,
+        }
+    }
+
+    /// Returns the character of the pending dead key, if any.
+    pub fn pending(&self) -> Option<char> {
+        self.pending
+    }
+
+    /// Handles a key press, returning the typed text.
+    ///
+    /// A dead key types nothing, but is composed with the next character.
+    /// When the next character can not be composed, both characters are typed.
+    /// A dead key followed by space or the same dead key types the character of the dead key.
+    /// Keys without characters, e.g. arrow keys, leave the pending dead key unchanged.
+    pub fn press(&mut self, scancode: i32, modifiers: ModifierState) -> String {
+        let symbol = match self.layout.symbol_with_modifiers(scancode, modifiers) {
+            Some(symbol) => symbol,
+            None => return String::new(),
+        };
+        match (self.pending.take(), symbol) {
+            (None, Symbol::Dead(dead)) => {
+                self.pending = Some(dead);
+                String::new()
+            }
+            (None, Symbol::Char(ch)) => ch.to_string(),
+            (Some(dead), Symbol::Dead(other)) if dead == other => dead.to_string(),
+            (Some(dead), Symbol::Char(' ')) => dead.to_string(),
+            (Some(dead), Symbol::Char(ch)) => match compose(dead, ch) {
+                Some(composed) => composed.to_string(),
+                None => [dead, ch].iter().collect(),
+            },
+            (Some(dead), Symbol::Dead(other)) => {
+                self.pending = Some(other);
+                dead.to_string()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifiers(sides: ModifierSide) -> ModifierState {
+        ModifierState {
+            sides,
+            ..ModifierState::new()
+        }
+    }
+
+    fn type_keystrokes(layout: Layout, keystrokes: &[Keystroke]) -> String {
+        let mut dead_keys = DeadKeys::new(layout);
+        keystrokes
+            .iter()
+            .map(|k| dead_keys.press(k.scancode, k.level.modifiers()))
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_symbols() {
+        // The physical Y key of a US keyboard.
+        assert_eq!(US.symbol(28, Level::Base), Some(Symbol::Char('y')));
+        assert_eq!(GERMAN.symbol(28, Level::Base), Some(Symbol::Char('z')));
+        assert_eq!(GERMAN.key(28), Key::Z);
+        assert_eq!(GERMAN.scancode(Key::Z), Some(28));
+        assert_eq!(FRENCH.key(20), Key::A);
+        assert_eq!(FRENCH.key(30), Key::D1);
+        assert_eq!(
+            FRENCH.symbol_with_modifiers(30, modifiers(ModifierSide::R_SHIFT)),
+            Some(Symbol::Char('1'))
+        );
+        assert_eq!(
+            GERMAN.symbol_with_modifiers(20, modifiers(ModifierSide::L_CTRL | ModifierSide::L_ALT)),
+            Some(Symbol::Char('@'))
+        );
+        assert_eq!(
+            GERMAN.symbol_with_modifiers(20, modifiers(ModifierSide::R_ALT)),
+            Some(Symbol::Char('@'))
+        );
+        assert_eq!(
+            US.symbol_with_modifiers(20, modifiers(ModifierSide::L_CTRL)),
+            None
+        );
+        assert_eq!(UK.symbol(32, Level::Shift), Some(Symbol::Char('£')));
+        assert_eq!(GERMAN.symbol(53, Level::Base), Some(Symbol::Dead('^')));
+        assert_eq!(US.symbol(53, Level::Base), Some(Symbol::Char('`')));
+        assert_eq!(Layout::from_name("fr"), Some(FRENCH));
+    }
+
+    #[test]
+    fn test_layout_keystrokes() {
+        assert_eq!(
+            GERMAN.keystrokes('z'),
+            Some(vec![Keystroke {
+                scancode: 28,
+                key: Key::Z,
+                level: Level::Base,
+            }])
+        );
+        assert_eq!(US.keystrokes('A').unwrap()[0].level, Level::Shift);
+        assert_eq!(US.keystrokes('ê'), None);
+        assert_eq!(GERMAN.keystrokes('ê').unwrap().len(), 2);
+        assert_eq!(FRENCH.keystrokes('ê').unwrap().len(), 2);
+        assert_eq!(FRENCH.keystrokes('é').unwrap().len(), 1);
+
+        for layout in LAYOUTS {
+            for text in &["Hello, World!\n", "a-b/c", "1234567890"] {
+                let keystrokes: Vec<Keystroke> = text
+                    .chars()
+                    .flat_map(|ch| layout.keystrokes(ch).unwrap())
+                    .collect();
+                assert_eq!(&type_keystrokes(*layout, &keystrokes), text);
+            }
+        }
+        for text in &["Grüße", "fête", "^`"] {
+            let keystrokes: Vec<Keystroke> = text
+                .chars()
+                .flat_map(|ch| GERMAN.keystrokes(ch).unwrap())
+                .collect();
+            assert_eq!(&type_keystrokes(GERMAN, &keystrokes), text);
+        }
+    }
+
+    #[test]
+    fn test_dead_keys() {
+        let mut dead_keys = DeadKeys::new(FRENCH);
+        let none = ModifierState::new();
+        assert_eq!(dead_keys.press(47, none), "");
+        assert_eq!(dead_keys.pending(), Some('^'));
+        assert_eq!(dead_keys.press(8, none), "ê");
+        assert_eq!(dead_keys.press(47, Level::Shift.modifiers()), "");
+        assert_eq!(dead_keys.press(28, none), "ÿ");
+        assert_eq!(dead_keys.press(47, none), "");
+        assert_eq!(dead_keys.press(5, none), "^b");
+        assert_eq!(dead_keys.press(47, none), "");
+        assert_eq!(dead_keys.press(44, none), "^");
+        assert_eq!(dead_keys.pending(), None);
+    }
+
+    #[test]
+    fn test_left_alt_shortcut() {
+        // Alt+Q is a shortcut, while Alt Gr+Q types `@` on the German layout.
+        let alt = modifiers(ModifierSide::L_ALT);
+        assert_eq!(Level::from_modifiers(alt), None);
+        assert_eq!(GERMAN.symbol_with_modifiers(20, alt), None);
+        let mut dead_keys = DeadKeys::new(GERMAN);
+        assert_eq!(dead_keys.press(20, alt), "");
+        assert_eq!(dead_keys.press(20, Level::AltGr.modifiers()), "@");
+        // A pending dead key is kept while pressing shortcuts.
+        assert_eq!(dead_keys.press(53, ModifierState::new()), "");
+        assert_eq!(dead_keys.press(20, alt), "");
+        assert_eq!(dead_keys.pending(), Some('^'));
+    }
+
+    #[test]
+    fn test_custom_layout() {
+        const DIGITS_DEAD: Layout = Layout::new(
+            "test",
+            &[(&[30, 31], ["12", "!´", ""]), (&[4], ["a", "A", "æ"])],
+            &[(31, Level::Shift)],
+        );
+        assert_eq!(DIGITS_DEAD.name(), "test");
+        assert_eq!(DIGITS_DEAD.key(4), Key::A);
+        assert_eq!(DIGITS_DEAD.symbol(4, Level::AltGr), Some(Symbol::Char('æ')));
+        assert_eq!(Layout::from_name("test"), None);
+        let keystrokes = DIGITS_DEAD.keystrokes('á').unwrap();
+        assert_eq!(keystrokes.len(), 2);
+        assert_eq!(type_keystrokes(DIGITS_DEAD, &keystrokes), "á");
+    }
+
+    #[test]
+    fn test_caps_lock() {
+        let caps = ModifierState {
+            locks: LockKey::CAPS_LOCK,
+            ..ModifierState::new()
+        };
+        let caps_shift = ModifierState {
+            locks: LockKey::CAPS_LOCK,
+            ..Level::Shift.modifiers()
+        };
+        assert_eq!(US.symbol_with_modifiers(4, caps), Some(Symbol::Char('A')));
+        assert_eq!(
+            US.symbol_with_modifiers(4, caps_shift),
+            Some(Symbol::Char('a'))
+        );
+        // Caps Lock only changes letters.
+        assert_eq!(US.symbol_with_modifiers(30, caps), Some(Symbol::Char('1')));
+        assert_eq!(
+            FRENCH.symbol_with_modifiers(31, caps),
+            Some(Symbol::Char('é'))
+        );
+        assert_eq!(
+            GERMAN.symbol_with_modifiers(47, caps),
+            Some(Symbol::Char('Ü'))
+        );
+        assert_eq!(
+            GERMAN.symbol_with_modifiers(
+                20,
+                ModifierState {
+                    locks: LockKey::CAPS_LOCK,
+                    ..Level::AltGr.modifiers()
+                }
+            ),
+            Some(Symbol::Char('@'))
+        );
+        let mut dead_keys = DeadKeys::new(FRENCH);
+        assert_eq!(dead_keys.press(47, caps), "");
+        assert_eq!(dead_keys.press(8, caps), "Ê");
+    }
+}
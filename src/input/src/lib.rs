@@ -22,29 +22,48 @@ extern crate serde_derive;
 
 use std::{any::Any, cmp::Ordering, fmt, path::PathBuf, sync::Arc};
 
-pub use controller::{ControllerAxisArgs, ControllerButton, ControllerHat};
+pub use controller::{
+    ConnectionState, ControllerAxisArgs, ControllerButton, ControllerConnectionArgs, ControllerHat,
+};
 pub use keyboard::Key;
 pub use mouse::MouseButton;
 
+pub mod coalesce;
+pub mod codec;
+pub mod context;
 pub mod controller;
+pub mod custom;
+pub mod dispatcher;
+pub mod emulation;
+pub mod gamepad;
 pub mod keyboard;
+pub mod keyboard_layout;
 pub mod mouse;
+pub mod simulator;
+pub mod touch;
 
 pub use after_render::{AfterRenderArgs, AfterRenderEvent};
 pub use button::{ButtonArgs, ButtonEvent, ButtonState, PressEvent, ReleaseEvent};
 pub use close::{CloseArgs, CloseEvent};
-pub use controller::ControllerAxisEvent;
+pub use controller::{ControllerAxisEvent, ControllerConnectionEvent};
 pub use cursor::CursorEvent;
+pub use custom::{CustomArgs, CustomEvent, CustomTypeError};
 use event_id::EventId;
 pub use focus::FocusEvent;
 pub use generic_event::GenericEvent;
 pub use idle::{IdleArgs, IdleEvent};
-pub use mouse::{MouseCursorEvent, MouseRelativeEvent, MouseScrollEvent};
+pub use ime::{Ime, ImeEvent};
+pub use mouse::{
+    MouseCursorEvent, MouseRelativeEvent, MouseScrollEvent, ScrollArgs, ScrollEvent, ScrollUnit,
+};
+pub use pen::{PenArgs, PenButtons, PenEvent};
 pub use render::{RenderArgs, RenderEvent};
 pub use resize::{ResizeArgs, ResizeEvent};
+pub use scale_factor::{ScaleFactorArgs, ScaleFactorEvent};
 pub use text::TextEvent;
-pub use touch::{Touch, TouchArgs, TouchEvent};
+pub use touch::{Gesture, GestureArgs, GestureEvent, GesturePhase, Touch, TouchArgs, TouchEvent};
 pub use update::{UpdateArgs, UpdateEvent};
+pub use window_state::{MaximizedEvent, MinimizedEvent, MovedEvent, OccludedEvent};
 
 pub mod event_id;
 pub mod generic_event;
@@ -53,13 +72,17 @@ mod after_render;
 mod button;
 mod close;
 mod cursor;
+mod event_trait;
 mod focus;
 mod idle;
+mod ime;
+mod pen;
 mod render;
 mod resize;
+mod scale_factor;
 mod text;
-mod touch;
 mod update;
+mod window_state;
 
 /// The type of time stamp.
 ///
@@ -88,10 +111,14 @@ pub enum Motion {
     MouseRelative([f64; 2]),
     /// Position in scroll ticks.
     MouseScroll([f64; 2]),
+    /// Scroll with unit and momentum phase, e.g. from a touchpad.
+    Scroll(ScrollArgs),
     /// Controller axis move event.
     ControllerAxis(ControllerAxisArgs),
     /// Touch event.
     Touch(TouchArgs),
+    /// Pen event.
+    Pen(PenArgs),
 }
 
 /// Stores controller hat state.
@@ -137,14 +164,30 @@ pub enum Input {
     Move(Motion),
     /// Text (usually from keyboard).
     Text(String),
+    /// Text composition with an input method editor (IME).
+    Ime(Ime),
     /// Window got resized.
     Resize(ResizeArgs),
+    /// The scale factor of the window changed.
+    ScaleFactor(ScaleFactorArgs),
     /// Window gained or lost focus.
     Focus(bool),
     /// Window gained or lost cursor.
     Cursor(bool),
+    /// Window moved to a new position in screen coordinates.
+    Moved([i32; 2]),
+    /// Window was minimized or restored.
+    Minimized(bool),
+    /// Window was maximized or restored.
+    Maximized(bool),
+    /// Window became hidden from view or visible again.
+    Occluded(bool),
+    /// A controller was added or removed.
+    Controller(ControllerConnectionArgs),
     /// A file is being dragged or dropped over the window.
     FileDrag(FileDrag),
+    /// A gesture was recognized.
+    Gesture(GestureArgs),
     /// Window closed.
     Close(CloseArgs),
 }
@@ -198,8 +241,8 @@ impl PartialEq for Event {
         use Event::*;
 
         match (self, other) {
-            (&Input(ref a, _), &Input(ref b, _)) => a == b,
-            (&Loop(ref a), &Loop(ref b)) => a == b,
+            (Input(a, _), Input(b, _)) => a == b,
+            (Loop(a), Loop(b)) => a == b,
             (_, _) => false,
         }
     }
@@ -210,9 +253,9 @@ impl PartialOrd for Event {
         use Event::*;
 
         match (self, other) {
-            (&Input(ref a, _), &Input(ref b, _)) => a.partial_cmp(b),
-            (&Loop(ref a), &Loop(ref b)) => a.partial_cmp(b),
-            (&Custom(ref a_id, _, _), &Custom(ref b_id, _, _)) => {
+            (Input(a, _), Input(b, _)) => a.partial_cmp(b),
+            (Loop(a), Loop(b)) => a.partial_cmp(b),
+            (Custom(a_id, _, _), Custom(b_id, _, _)) => {
                 let res = a_id.partial_cmp(b_id);
                 if res == Some(Ordering::Equal) {
                     None
@@ -264,6 +307,12 @@ impl From<ControllerAxisArgs> for Input {
     }
 }
 
+impl From<ControllerConnectionArgs> for Input {
+    fn from(args: ControllerConnectionArgs) -> Self {
+        Input::Controller(args)
+    }
+}
+
 impl From<TouchArgs> for Motion {
     fn from(args: TouchArgs) -> Self {
         Motion::Touch(args)
@@ -276,6 +325,30 @@ impl From<TouchArgs> for Input {
     }
 }
 
+impl From<PenArgs> for Motion {
+    fn from(args: PenArgs) -> Self {
+        Motion::Pen(args)
+    }
+}
+
+impl From<PenArgs> for Input {
+    fn from(args: PenArgs) -> Self {
+        Input::Move(Motion::Pen(args))
+    }
+}
+
+impl From<ScrollArgs> for Motion {
+    fn from(args: ScrollArgs) -> Self {
+        Motion::Scroll(args)
+    }
+}
+
+impl From<ScrollArgs> for Input {
+    fn from(args: ScrollArgs) -> Self {
+        Input::Move(Motion::Scroll(args))
+    }
+}
+
 impl From<Motion> for Input {
     fn from(motion: Motion) -> Self {
         Input::Move(motion)
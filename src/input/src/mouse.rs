@@ -1,6 +1,12 @@
 //! Back-end agnostic mouse buttons.
 
-use crate::{Event, Input, Motion};
+use std::collections::HashMap;
+
+use viewport::Viewport;
+
+use crate::{
+    scale_factor, Button, ButtonState, Event, GenericEvent, GesturePhase, Input, Motion, TimeStamp,
+};
 
 /// Represent a mouse button.
 #[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
@@ -86,6 +92,15 @@ pub trait MouseCursorEvent: Sized {
     fn mouse_cursor_args(&self) -> Option<[f64; 2]> {
         self.mouse_cursor(|pos| pos)
     }
+    /// Returns mouse cursor position in draw pixels.
+    ///
+    /// Uses the window and draw size of the viewport,
+    /// e.g. from [`ResizeArgs::viewport`](crate::ResizeArgs::viewport).
+    fn mouse_cursor_pixels(&self, viewport: &Viewport) -> Option<[f64; 2]> {
+        self.mouse_cursor(|pos| {
+            scale_factor::points_to_pixels(viewport.window_size, viewport.draw_size, pos)
+        })
+    }
 }
 
 impl MouseCursorEvent for Event {
@@ -154,7 +169,64 @@ impl MouseRelativeEvent for Event {
     }
 }
 
+/// The number of pixels per line used to convert precise scroll deltas to scroll ticks.
+pub const PIXELS_PER_LINE: f64 = 20.0;
+/// The number of lines per page used to convert page scroll deltas to scroll ticks.
+pub const LINES_PER_PAGE: f64 = 3.0;
+
+/// The unit of a scroll delta.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum ScrollUnit {
+    /// Lines, or scroll ticks of a mouse wheel.
+    Lines,
+    /// Pixels, e.g. from a touchpad.
+    Pixels,
+    /// Pages.
+    Pages,
+}
+
+/// Scroll arguments.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct ScrollArgs {
+    /// The scroll delta.
+    pub delta: [f64; 2],
+    /// The unit of the scroll delta.
+    pub unit: ScrollUnit,
+    /// Whether the delta comes from a continuous device, e.g. a touchpad.
+    pub precise: bool,
+    /// The momentum phase, if the scroll is continued by the system after lifting the fingers.
+    pub momentum: Option<GesturePhase>,
+}
+
+impl ScrollArgs {
+    /// Create a new `ScrollArgs` object. Intended for use by backends when emitting events.
+    ///
+    /// The scroll is precise when the unit is pixels.
+    pub fn new(delta: [f64; 2], unit: ScrollUnit) -> ScrollArgs {
+        ScrollArgs {
+            delta,
+            unit,
+            precise: unit == ScrollUnit::Pixels,
+            momentum: None,
+        }
+    }
+
+    /// Returns the delta in scroll ticks.
+    ///
+    /// Uses [`PIXELS_PER_LINE`] and [`LINES_PER_PAGE`] to convert other units.
+    pub fn ticks(&self) -> [f64; 2] {
+        let factor = match self.unit {
+            ScrollUnit::Lines => 1.0,
+            ScrollUnit::Pixels => 1.0 / PIXELS_PER_LINE,
+            ScrollUnit::Pages => LINES_PER_PAGE,
+        };
+        [self.delta[0] * factor, self.delta[1] * factor]
+    }
+}
+
 /// The scroll of the mouse wheel.
+///
+/// Precise scroll events are converted to scroll ticks, see [`ScrollArgs::ticks`].
 pub trait MouseScrollEvent: Sized {
     /// Creates a mouse scroll event.
     ///
@@ -189,11 +261,311 @@ impl MouseScrollEvent for Event {
     {
         match *self {
             Event::Input(Input::Move(Motion::MouseScroll(pos)), _) => Some(f(pos)),
+            Event::Input(Input::Move(Motion::Scroll(ref args)), _) => Some(f(args.ticks())),
             _ => None,
         }
     }
 }
 
+/// The scroll of the mouse wheel or touchpad, with unit and momentum phase.
+///
+/// Scroll tick events are reported with unit [`ScrollUnit::Lines`].
+pub trait ScrollEvent: Sized {
+    /// Creates a scroll event.
+    ///
+    /// Preserves time stamp from original input event, if any.
+    fn from_scroll_args(args: &ScrollArgs, old_event: &Self) -> Option<Self>;
+    /// Calls a closure if this is a scroll event.
+    fn scroll<U, F>(&self, f: F) -> Option<U>
+    where
+        F: FnMut(&ScrollArgs) -> U;
+    /// Returns scroll arguments.
+    fn scroll_args(&self) -> Option<ScrollArgs> {
+        self.scroll(|args| *args)
+    }
+}
+
+impl ScrollEvent for Event {
+    fn from_scroll_args(args: &ScrollArgs, old_event: &Self) -> Option<Self> {
+        let timestamp = if let Event::Input(_, x) = old_event {
+            *x
+        } else {
+            None
+        };
+        Some(Event::Input(Input::Move(Motion::Scroll(*args)), timestamp))
+    }
+
+    fn scroll<U, F>(&self, mut f: F) -> Option<U>
+    where
+        F: FnMut(&ScrollArgs) -> U,
+    {
+        match *self {
+            Event::Input(Input::Move(Motion::MouseScroll(pos)), _) => {
+                Some(f(&ScrollArgs::new(pos, ScrollUnit::Lines)))
+            }
+            Event::Input(Input::Move(Motion::Scroll(ref args)), _) => Some(f(args)),
+            _ => None,
+        }
+    }
+}
+
+/// A mouse interaction recognized by [`MouseGestures`].
+///
+/// Positions are in window coordinates, like mouse cursor events.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub enum MouseGesture {
+    /// A mouse button was pressed and released without dragging.
+    Click {
+        /// The mouse button.
+        button: MouseButton,
+        /// The position of the click.
+        position: [f64; 2],
+        /// The number of clicks in quick succession, e.g. `2` for a double click.
+        count: u32,
+    },
+    /// The mouse moved beyond the drag distance while a button was held.
+    DragStart {
+        /// The mouse button.
+        button: MouseButton,
+        /// The position where the button was pressed.
+        origin: [f64; 2],
+        /// The current position.
+        position: [f64; 2],
+    },
+    /// The mouse moved while dragging.
+    Drag {
+        /// The mouse button.
+        button: MouseButton,
+        /// The position where the button was pressed.
+        origin: [f64; 2],
+        /// The current position.
+        position: [f64; 2],
+        /// The movement since the last drag event.
+        delta: [f64; 2],
+    },
+    /// The button was released or the window lost focus while dragging.
+    DragEnd {
+        /// The mouse button.
+        button: MouseButton,
+        /// The position where the button was pressed.
+        origin: [f64; 2],
+        /// The last position.
+        position: [f64; 2],
+    },
+    /// The mouse rested at a position without buttons pressed.
+    Hover {
+        /// The position of the mouse cursor.
+        position: [f64; 2],
+    },
+}
+
+/// Thresholds used to recognize mouse gestures.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct MouseGestureSettings {
+    /// The maximum time in milliseconds between clicks to be counted together.
+    pub double_click_time: TimeStamp,
+    /// The distance the mouse must move while a button is held to start dragging.
+    pub drag_distance: f64,
+    /// The time in milliseconds the mouse must rest to hover.
+    pub hover_time: TimeStamp,
+}
+
+impl MouseGestureSettings {
+    /// Creates new mouse gesture settings with defaults.
+    ///
+    /// - `double_click_time`: 500
+    /// - `drag_distance`: 4.0
+    /// - `hover_time`: 500
+    pub fn new() -> MouseGestureSettings {
+        MouseGestureSettings {
+            double_click_time: 500,
+            drag_distance: 4.0,
+            hover_time: 500,
+        }
+    }
+}
+
+impl Default for MouseGestureSettings {
+    fn default() -> MouseGestureSettings {
+        MouseGestureSettings::new()
+    }
+}
+
+/// Stores the state of a held mouse button.
+#[derive(Copy, Clone, Debug)]
+struct Held {
+    origin: [f64; 2],
+    dragging: bool,
+}
+
+/// Recognizes clicks, drags and hovering from mouse events.
+///
+/// Time is measured from the time stamps of input events.
+/// Between input events, time is advanced by update events,
+/// which is required to recognize hovering.
+#[derive(Clone, Debug, Default)]
+pub struct MouseGestures {
+    /// The thresholds used to recognize gestures.
+    pub settings: MouseGestureSettings,
+    position: [f64; 2],
+    held: HashMap<MouseButton, Held>,
+    last_click: Option<(MouseButton, f64, [f64; 2], u32)>,
+    /// The time of the last movement, or `None` when hovering is not possible.
+    rest: Option<f64>,
+    /// Current time in milliseconds.
+    time: f64,
+}
+
+impl MouseGestures {
+    /// Creates a new mouse gesture recognizer.
+    pub fn new(settings: MouseGestureSettings) -> MouseGestures {
+        MouseGestures {
+            settings,
+            position: [0.0; 2],
+            held: HashMap::new(),
+            last_click: None,
+            rest: None,
+            time: 0.0,
+        }
+    }
+
+    /// Returns the current position of the mouse cursor.
+    pub fn position(&self) -> [f64; 2] {
+        self.position
+    }
+
+    /// Returns `true` if a mouse button is dragging.
+    pub fn is_dragging(&self, button: MouseButton) -> bool {
+        self.held.get(&button).map(|h| h.dragging).unwrap_or(false)
+    }
+
+    /// Handles an event, calling the closure for each recognized gesture.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(MouseGesture),
+    {
+        if let Some(t) = e.time_stamp() {
+            self.time = self.time.max(t as f64);
+        }
+        if let Some(args) = e.update_args() {
+            self.time += args.dt * 1000.0;
+            if let Some(rest) = self.rest {
+                if self.time - rest >= self.settings.hover_time as f64 {
+                    self.rest = None;
+                    f(MouseGesture::Hover {
+                        position: self.position,
+                    });
+                }
+            }
+        }
+        if let Some(pos) = e.mouse_cursor_args() {
+            let delta = [pos[0] - self.position[0], pos[1] - self.position[1]];
+            self.position = pos;
+            self.rest = if self.held.is_empty() {
+                Some(self.time)
+            } else {
+                None
+            };
+            let mut held: Vec<_> = self.held.iter_mut().collect();
+            held.sort_by_key(|&(&button, _)| button);
+            for (&button, held) in held {
+                if held.dragging {
+                    f(MouseGesture::Drag {
+                        button,
+                        origin: held.origin,
+                        position: pos,
+                        delta,
+                    });
+                } else {
+                    let dx = pos[0] - held.origin[0];
+                    let dy = pos[1] - held.origin[1];
+                    if (dx * dx + dy * dy).sqrt() >= self.settings.drag_distance {
+                        held.dragging = true;
+                        f(MouseGesture::DragStart {
+                            button,
+                            origin: held.origin,
+                            position: pos,
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(args) = e.button_args() {
+            if let Button::Mouse(button) = args.button {
+                match args.state {
+                    ButtonState::Press => {
+                        if args.repeat {
+                            return;
+                        }
+                        self.rest = None;
+                        self.held.insert(
+                            button,
+                            Held {
+                                origin: self.position,
+                                dragging: false,
+                            },
+                        );
+                    }
+                    ButtonState::Release => {
+                        let held = match self.held.remove(&button) {
+                            Some(x) => x,
+                            None => return,
+                        };
+                        if held.dragging {
+                            f(MouseGesture::DragEnd {
+                                button,
+                                origin: held.origin,
+                                position: self.position,
+                            });
+                        } else {
+                            let settings = &self.settings;
+                            let (time, position) = (self.time, self.position);
+                            let count = match self.last_click {
+                                Some((last_button, last_time, last_pos, count))
+                                    if last_button == button
+                                        && time - last_time
+                                            <= settings.double_click_time as f64
+                                        && (position[0] - last_pos[0]).abs()
+                                            < settings.drag_distance
+                                        && (position[1] - last_pos[1]).abs()
+                                            < settings.drag_distance =>
+                                {
+                                    count + 1
+                                }
+                                _ => 1,
+                            };
+                            self.last_click = Some((button, time, position, count));
+                            f(MouseGesture::Click {
+                                button,
+                                position,
+                                count,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        let left = e.cursor_args() == Some(false) || e.focus_args() == Some(false);
+        if left {
+            self.rest = None;
+        }
+        if e.focus_args() == Some(false) {
+            let mut held: Vec<_> = self.held.drain().collect();
+            held.sort_by_key(|&(button, _)| button);
+            for (button, held) in held {
+                if held.dragging {
+                    f(MouseGesture::DragEnd {
+                        button,
+                        origin: held.origin,
+                        position: self.position,
+                    });
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod mouse_event_tests {
     use super::*;
@@ -212,6 +584,18 @@ mod mouse_event_tests {
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn test_mouse_cursor_pixels() {
+        use crate::ResizeArgs;
+
+        let e: Event = Motion::MouseCursor([10.0, 5.0]).into();
+        let args = ResizeArgs {
+            window_size: [100.0, 50.0],
+            draw_size: [200, 100],
+        };
+        assert_eq!(e.mouse_cursor_pixels(&args.viewport()), Some([20.0, 10.0]));
+    }
+
     #[test]
     fn test_input_mouse_relative() {
         use super::super::Motion;
@@ -239,4 +623,129 @@ mod mouse_event_tests {
             .unwrap();
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn test_input_scroll() {
+        use super::super::Motion;
+
+        let e: Event = (Motion::MouseScroll([0.0, 0.0]), Some(0)).into();
+        let mut args = ScrollArgs::new([0.0, 40.0], ScrollUnit::Pixels);
+        args.momentum = Some(GesturePhase::Update);
+        let a: Option<Event> = ScrollEvent::from_scroll_args(&args, &e);
+        let b: Option<Event> = a
+            .clone()
+            .unwrap()
+            .scroll(|args| ScrollEvent::from_scroll_args(args, a.as_ref().unwrap()))
+            .unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.unwrap().mouse_scroll_args(), Some([0.0, 2.0]));
+        assert_eq!(
+            e.scroll_args(),
+            Some(ScrollArgs::new([0.0, 0.0], ScrollUnit::Lines))
+        );
+    }
+}
+
+#[cfg(test)]
+mod mouse_gesture_tests {
+    use super::*;
+    use crate::{ButtonArgs, UpdateArgs};
+
+    fn button(state: ButtonState, time: TimeStamp) -> Event {
+        (
+            Input::Button(ButtonArgs::new(state, MouseButton::Left.into(), None)),
+            Some(time),
+        )
+            .into()
+    }
+
+    fn cursor(pos: [f64; 2], time: TimeStamp) -> Event {
+        (Input::Move(Motion::MouseCursor(pos)), Some(time)).into()
+    }
+
+    fn gestures(mouse: &mut MouseGestures, events: &[Event]) -> Vec<MouseGesture> {
+        let mut out = vec![];
+        for e in events {
+            mouse.event(e, |g| out.push(g));
+        }
+        out
+    }
+
+    #[test]
+    fn test_click() {
+        let mut mouse = MouseGestures::new(MouseGestureSettings::new());
+        let p = [10.0, 10.0];
+        let events = [
+            cursor(p, 0),
+            button(ButtonState::Press, 100),
+            button(ButtonState::Release, 150),
+            button(ButtonState::Press, 200),
+            cursor([11.0, 10.0], 220),
+            button(ButtonState::Release, 250),
+            button(ButtonState::Press, 1000),
+            button(ButtonState::Release, 1050),
+        ];
+        assert_eq!(
+            gestures(&mut mouse, &events),
+            vec![
+                MouseGesture::Click {
+                    button: MouseButton::Left,
+                    position: p,
+                    count: 1
+                },
+                MouseGesture::Click {
+                    button: MouseButton::Left,
+                    position: [11.0, 10.0],
+                    count: 2
+                },
+                MouseGesture::Click {
+                    button: MouseButton::Left,
+                    position: [11.0, 10.0],
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drag_and_hover() {
+        let mut mouse = MouseGestures::new(MouseGestureSettings::new());
+        let b = MouseButton::Left;
+        let events = [
+            cursor([0.0, 0.0], 0),
+            button(ButtonState::Press, 0),
+            cursor([2.0, 0.0], 10),
+            cursor([5.0, 0.0], 20),
+            cursor([8.0, 0.0], 30),
+            button(ButtonState::Release, 40),
+            cursor([9.0, 0.0], 50),
+            UpdateArgs { dt: 0.25 }.into(),
+            UpdateArgs { dt: 0.25 }.into(),
+            UpdateArgs { dt: 0.25 }.into(),
+        ];
+        assert_eq!(
+            gestures(&mut mouse, &events),
+            vec![
+                MouseGesture::DragStart {
+                    button: b,
+                    origin: [0.0, 0.0],
+                    position: [5.0, 0.0]
+                },
+                MouseGesture::Drag {
+                    button: b,
+                    origin: [0.0, 0.0],
+                    position: [8.0, 0.0],
+                    delta: [3.0, 0.0]
+                },
+                MouseGesture::DragEnd {
+                    button: b,
+                    origin: [0.0, 0.0],
+                    position: [8.0, 0.0]
+                },
+                MouseGesture::Hover {
+                    position: [9.0, 0.0]
+                },
+            ]
+        );
+    }
 }
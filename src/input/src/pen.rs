@@ -0,0 +1,86 @@
+use crate::{Input, Motion};
+
+bitflags!(
+    /// Buttons on the barrel of a pen.
+    #[derive(Default, Deserialize, Serialize)]
+    pub struct PenButtons: u8 {
+        /// The barrel button closest to the tip.
+        const PRIMARY   = 0b0000_0001;
+        /// The second barrel button.
+        const SECONDARY = 0b0000_0010;
+        /// The third barrel button.
+        const TERTIARY  = 0b0000_0100;
+    }
+);
+
+/// Pen arguments, for example from a drawing tablet or a stylus on a touch screen.
+///
+/// The pen is in range while it hovers close to the tablet or touches it.
+/// An event with `in_range` set to `false` is emitted when the pen leaves the tablet.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct PenArgs {
+    /// A unique identifier for the pen device.
+    pub device: i64,
+    /// Position in window coordinates.
+    pub position: [f64; 2],
+    /// Whether the tip touches the tablet.
+    pub touching: bool,
+    /// The pressure of the tip, normalized 0..1.
+    ///
+    /// This is zero while hovering.
+    pub pressure: f64,
+    /// The tilt in degrees from perpendicular, in the x and y direction.
+    ///
+    /// Positive x tilts to the right, positive y tilts towards the user.
+    pub tilt: [f64; 2],
+    /// The rotation in degrees around the pen axis (twist), clockwise 0..360.
+    pub rotation: f64,
+    /// The hover distance from the tablet, normalized 0..1.
+    ///
+    /// Set to `None` when the device does not report distance.
+    pub distance: Option<f64>,
+    /// Whether the pen is close enough to the tablet to be tracked.
+    pub in_range: bool,
+    /// Whether the eraser end of the pen is used.
+    pub eraser: bool,
+    /// The barrel buttons that are held down.
+    pub buttons: PenButtons,
+}
+
+impl PenArgs {
+    /// Creates arguments for a pen in range without tilt, rotation or buttons.
+    ///
+    /// The pen touches the tablet when the pressure is above zero.
+    pub fn new(device: i64, position: [f64; 2], pressure: f64) -> PenArgs {
+        PenArgs {
+            device,
+            position,
+            touching: pressure > 0.0,
+            pressure,
+            tilt: [0.0, 0.0],
+            rotation: 0.0,
+            distance: None,
+            in_range: true,
+            eraser: false,
+            buttons: PenButtons::empty(),
+        }
+    }
+}
+
+crate::event_trait! {
+    /// When a pen moves, changes pressure or buttons, or enters or leaves range.
+    pub trait PenEvent {
+        args: &PenArgs,
+        from_args: from_pen_args,
+        accessor: pen,
+        args_getter: pen_args,
+        event: Input(Input::Move, Motion::Pen),
+        test: test_input_pen(PenArgs {
+            tilt: [10.0, -20.0],
+            rotation: 90.0,
+            eraser: true,
+            buttons: PenButtons::PRIMARY,
+            ..PenArgs::new(0, [1.0, 2.0], 0.5)
+        }),
+    }
+}
@@ -1,6 +1,6 @@
 use viewport::Viewport;
 
-use crate::{Event, Loop};
+use crate::{scale_factor, Event, Loop};
 
 /// Render arguments.
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Deserialize, Serialize)]
@@ -22,6 +22,16 @@ impl RenderArgs {
             draw_size: self.draw_size,
         }
     }
+
+    /// Converts a position in window points to draw pixels.
+    pub fn points_to_pixels(&self, pos: [f64; 2]) -> [f64; 2] {
+        scale_factor::points_to_pixels(self.window_size, self.draw_size, pos)
+    }
+
+    /// Converts a position in draw pixels to window points.
+    pub fn pixels_to_points(&self, pos: [f64; 2]) -> [f64; 2] {
+        scale_factor::pixels_to_points(self.window_size, self.draw_size, pos)
+    }
 }
 
 /// When the next frame should be rendered.
@@ -1,6 +1,6 @@
 use viewport::Viewport;
 
-use crate::{Event, Input};
+use crate::{scale_factor, Event, Input};
 
 /// Resize arguments.
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Deserialize, Serialize)]
@@ -20,6 +20,16 @@ impl ResizeArgs {
             draw_size: self.draw_size,
         }
     }
+
+    /// Converts a position in window points to draw pixels.
+    pub fn points_to_pixels(&self, pos: [f64; 2]) -> [f64; 2] {
+        scale_factor::points_to_pixels(self.window_size, self.draw_size, pos)
+    }
+
+    /// Converts a position in draw pixels to window points.
+    pub fn pixels_to_points(&self, pos: [f64; 2]) -> [f64; 2] {
+        scale_factor::pixels_to_points(self.window_size, self.draw_size, pos)
+    }
 }
 
 /// When the window is resized.
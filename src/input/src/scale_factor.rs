@@ -0,0 +1,133 @@
+use viewport::Viewport;
+
+use crate::Input;
+
+/// Returns the number of pixels per point along each axis.
+///
+/// Returns `1.0` along an axis where the window size or draw size is zero,
+/// e.g. when the window is minimized.
+pub(crate) fn pixels_per_point(window_size: [f64; 2], draw_size: [u32; 2]) -> [f64; 2] {
+    let scale = |w: f64, d: u32| {
+        if w > 0.0 && d > 0 {
+            d as f64 / w
+        } else {
+            1.0
+        }
+    };
+    [
+        scale(window_size[0], draw_size[0]),
+        scale(window_size[1], draw_size[1]),
+    ]
+}
+
+/// Converts a position in window points to draw pixels.
+pub(crate) fn points_to_pixels(
+    window_size: [f64; 2],
+    draw_size: [u32; 2],
+    pos: [f64; 2],
+) -> [f64; 2] {
+    let scale = pixels_per_point(window_size, draw_size);
+    [pos[0] * scale[0], pos[1] * scale[1]]
+}
+
+/// Converts a position in draw pixels to window points.
+pub(crate) fn pixels_to_points(
+    window_size: [f64; 2],
+    draw_size: [u32; 2],
+    pos: [f64; 2],
+) -> [f64; 2] {
+    let scale = pixels_per_point(window_size, draw_size);
+    [pos[0] / scale[0], pos[1] / scale[1]]
+}
+
+/// Scale factor arguments.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Deserialize, Serialize)]
+pub struct ScaleFactorArgs {
+    /// The new number of pixels per point, as reported by the window backend.
+    ///
+    /// This is informational. Conversions between points and pixels
+    /// use the ratio of `draw_size` to `window_size` along each axis,
+    /// the same way as render and resize events,
+    /// so they stay consistent with the rendered area
+    /// when the backend rounds the draw size.
+    pub scale_factor: f64,
+    /// The width and height of rendered area in points.
+    pub window_size: [f64; 2],
+    /// The width and height of rendered area in pixels.
+    pub draw_size: [u32; 2],
+}
+
+impl ScaleFactorArgs {
+    /// Returns viewport information filling entire render area.
+    pub fn viewport(&self) -> Viewport {
+        Viewport {
+            rect: [0, 0, self.draw_size[0] as i32, self.draw_size[1] as i32],
+            window_size: self.window_size,
+            draw_size: self.draw_size,
+        }
+    }
+
+    /// Converts a position in window points to draw pixels.
+    ///
+    /// Uses the window size and draw size, not `scale_factor`.
+    pub fn points_to_pixels(&self, pos: [f64; 2]) -> [f64; 2] {
+        points_to_pixels(self.window_size, self.draw_size, pos)
+    }
+
+    /// Converts a position in draw pixels to window points.
+    ///
+    /// Uses the window size and draw size, not `scale_factor`.
+    pub fn pixels_to_points(&self, pos: [f64; 2]) -> [f64; 2] {
+        pixels_to_points(self.window_size, self.draw_size, pos)
+    }
+}
+
+crate::event_trait! {
+    /// When the scale factor of the window changes,
+    /// e.g. when moved to a monitor with a different pixel density.
+    pub trait ScaleFactorEvent {
+        args: &ScaleFactorArgs,
+        from_args: from_scale_factor_args,
+        accessor: scale_factor,
+        args_getter: scale_factor_args,
+        event: Input(Input::ScaleFactor),
+        test: test_input_scale_factor(ScaleFactorArgs {
+            scale_factor: 2.0,
+            window_size: [100.0, 100.0],
+            draw_size: [200, 200],
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_pixels() {
+        let args = ScaleFactorArgs {
+            scale_factor: 2.0,
+            window_size: [100.0, 50.0],
+            draw_size: [200, 100],
+        };
+        assert_eq!(args.points_to_pixels([10.0, 5.0]), [20.0, 10.0]);
+        assert_eq!(args.pixels_to_points([20.0, 10.0]), [10.0, 5.0]);
+        assert_eq!(points_to_pixels([0.0, 0.0], [0, 0], [1.0, 2.0]), [1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_points_pixels_minimized() {
+        // Minimized windows might report a zero draw size.
+        let args = ScaleFactorArgs {
+            scale_factor: 2.0,
+            window_size: [100.0, 50.0],
+            draw_size: [0, 0],
+        };
+        assert_eq!(
+            pixels_per_point(args.window_size, args.draw_size),
+            [1.0, 1.0]
+        );
+        assert_eq!(args.pixels_to_points([1.0, 2.0]), [1.0, 2.0]);
+        assert_eq!(args.points_to_pixels([1.0, 2.0]), [1.0, 2.0]);
+    }
+}
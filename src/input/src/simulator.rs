@@ -0,0 +1,533 @@
+//! Simulated user input for testing.
+//!
+//! A [`Simulator`] produces the events a window backend would emit
+//! for high-level actions, such as typing text or dragging with the mouse.
+//! Events get increasing time stamps, as if a user performed the actions.
+//!
+//! ```ignore
+//! let mut sim = Simulator::new(SimulatorSettings::new());
+//! sim.click(MouseButton::Left, [10.0, 20.0]);
+//! sim.type_text("Hello");
+//! sim.chord(ModifierKey::CTRL, Key::S);
+//! for e in sim.take_events() {
+//!     widget.event(&e);
+//! }
+//! ```
+//!
+//! To run an application with simulated input,
+//! push the events to a `NoWindow` from `pistoncore-window`.
+
+use crate::{
+    keyboard::ModifierKey,
+    keyboard_layout::{Layout, Level, US},
+    Button, ButtonArgs, ButtonState, ControllerAxisArgs, Event, Input, Key, Motion, MouseButton,
+    ScrollArgs, ScrollUnit, TimeStamp, Touch, TouchArgs,
+};
+
+/// Timing of simulated input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SimulatorSettings {
+    /// Milliseconds between events of an action, e.g. a key press and its text.
+    pub event_interval: TimeStamp,
+    /// Milliseconds a key or mouse button is held down.
+    pub hold_time: TimeStamp,
+    /// Milliseconds between motion steps.
+    pub step_interval: TimeStamp,
+    /// Number of steps when moving the mouse cursor, touches or controller sticks.
+    pub steps: u32,
+    /// The keyboard layout used to type text.
+    pub layout: Layout,
+}
+
+impl SimulatorSettings {
+    /// Creates new simulator settings.
+    ///
+    /// - `event_interval`: 10 ms
+    /// - `hold_time`: 80 ms
+    /// - `step_interval`: 16 ms
+    /// - `steps`: 8
+    /// - `layout`: US
+    pub fn new() -> SimulatorSettings {
+        SimulatorSettings {
+            event_interval: 10,
+            hold_time: 80,
+            step_interval: 16,
+            steps: 8,
+            layout: US,
+        }
+    }
+}
+
+impl Default for SimulatorSettings {
+    fn default() -> SimulatorSettings {
+        SimulatorSettings::new()
+    }
+}
+
+/// Returns the left modifier keys of a modifier combination.
+fn modifier_keys(modifiers: ModifierKey) -> Vec<Key> {
+    let mut keys = vec![];
+    if modifiers.contains(ModifierKey::CTRL) {
+        keys.push(Key::LCtrl);
+    }
+    if modifiers.contains(ModifierKey::SHIFT) {
+        keys.push(Key::LShift);
+    }
+    if modifiers.contains(ModifierKey::ALT) {
+        keys.push(Key::LAlt);
+    }
+    if modifiers.contains(ModifierKey::GUI) {
+        keys.push(Key::LGui);
+    }
+    keys
+}
+
+fn lerp(a: [f64; 2], b: [f64; 2], t: f64) -> [f64; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
+
+/// Produces events for high-level user actions.
+///
+/// The mouse cursor starts at `[0.0, 0.0]` and the clock at 0 milliseconds.
+#[derive(Clone, Debug)]
+pub struct Simulator {
+    /// The settings used by the simulator.
+    pub settings: SimulatorSettings,
+    time: TimeStamp,
+    cursor: [f64; 2],
+    events: Vec<Event>,
+}
+
+impl Simulator {
+    /// Creates a new simulator.
+    pub fn new(settings: SimulatorSettings) -> Simulator {
+        Simulator {
+            settings,
+            time: 0,
+            cursor: [0.0; 2],
+            events: vec![],
+        }
+    }
+
+    /// Returns the time stamp of the next event.
+    pub fn time(&self) -> TimeStamp {
+        self.time
+    }
+
+    /// Returns the position of the mouse cursor.
+    pub fn cursor(&self) -> [f64; 2] {
+        self.cursor
+    }
+
+    /// Returns the events produced so far.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Removes and returns the events produced so far.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Adds an input event at the current time, then advances the clock.
+    ///
+    /// The clock advances at least 1 ms, such that every event has a later time stamp.
+    pub fn push<I: Into<Input>>(&mut self, input: I, dt: TimeStamp) -> &mut Self {
+        self.events
+            .push(Event::Input(input.into(), Some(self.time)));
+        self.wait(dt.max(1))
+    }
+
+    /// Advances the clock without producing events.
+    pub fn wait(&mut self, dt: TimeStamp) -> &mut Self {
+        self.time = self.time.saturating_add(dt);
+        self
+    }
+
+    /// Adds input events 1 ms apart, then waits the rest of the interval since the first event.
+    ///
+    /// Used for events happening together, e.g. several fingers moving,
+    /// since every event gets a later time stamp than the previous one.
+    fn push_group<I>(&mut self, inputs: I, interval: TimeStamp) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Input>,
+    {
+        let start = self.time;
+        for input in inputs {
+            self.push(input, 1);
+        }
+        let elapsed = self.time - start;
+        self.wait(interval.saturating_sub(elapsed))
+    }
+
+    fn button(&mut self, state: ButtonState, button: Button, dt: TimeStamp) -> &mut Self {
+        self.push(ButtonArgs::new(state, button, None), dt)
+    }
+
+    /// Presses a button.
+    pub fn press<B: Into<Button>>(&mut self, button: B) -> &mut Self {
+        let dt = self.settings.event_interval;
+        self.button(ButtonState::Press, button.into(), dt)
+    }
+
+    /// Releases a button.
+    pub fn release<B: Into<Button>>(&mut self, button: B) -> &mut Self {
+        let dt = self.settings.event_interval;
+        self.button(ButtonState::Release, button.into(), dt)
+    }
+
+    /// Presses and releases a key.
+    pub fn key(&mut self, key: Key) -> &mut Self {
+        let hold_time = self.settings.hold_time;
+        self.button(ButtonState::Press, key.into(), hold_time);
+        self.release(key)
+    }
+
+    /// Presses a key while holding down modifier keys, e.g. `Ctrl+S`.
+    ///
+    /// Modifiers are pressed in the order Ctrl, Shift, Alt and GUI,
+    /// and released in reverse order.
+    pub fn chord(&mut self, modifiers: ModifierKey, key: Key) -> &mut Self {
+        let keys = modifier_keys(modifiers);
+        for &modifier in &keys {
+            self.press(modifier);
+        }
+        self.key(key);
+        for &modifier in keys.iter().rev() {
+            self.release(modifier);
+        }
+        self
+    }
+
+    /// Types text, pressing a key, emitting text and releasing the key for every character.
+    ///
+    /// Keys follow the keyboard layout of the settings, with scancodes.
+    /// Shift and Alt Gr are held down when needed, where Alt Gr is the right Alt key.
+    /// Characters typed with dead keys emit text after the last key press.
+    /// Characters without keys in the layout only emit text.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        let interval = self.settings.event_interval;
+        let hold_time = self.settings.hold_time;
+        for ch in text.chars() {
+            let keystrokes = match self.settings.layout.keystrokes(ch) {
+                Some(keystrokes) => keystrokes,
+                None => {
+                    self.push(Input::Text(ch.to_string()), interval);
+                    continue;
+                }
+            };
+            let last = keystrokes.len() - 1;
+            for (i, keystroke) in keystrokes.into_iter().enumerate() {
+                let modifier = match keystroke.level {
+                    Level::Base => None,
+                    Level::Shift => Some(Key::LShift),
+                    Level::AltGr => Some(Key::RAlt),
+                };
+                if let Some(modifier) = modifier {
+                    self.press(modifier);
+                }
+                let press = ButtonArgs::new(
+                    ButtonState::Press,
+                    keystroke.key.into(),
+                    Some(keystroke.scancode),
+                );
+                self.push(press, interval);
+                // Tab and return do not produce text events.
+                if i == last && !ch.is_control() {
+                    self.push(Input::Text(ch.to_string()), hold_time);
+                } else {
+                    self.wait(hold_time);
+                }
+                let release = ButtonArgs::new(
+                    ButtonState::Release,
+                    keystroke.key.into(),
+                    Some(keystroke.scancode),
+                );
+                self.push(release, interval);
+                if let Some(modifier) = modifier {
+                    self.release(modifier);
+                }
+            }
+        }
+        self
+    }
+
+    /// Moves the mouse cursor in steps to a position.
+    pub fn move_to(&mut self, pos: [f64; 2]) -> &mut Self {
+        let start = self.cursor;
+        let steps = self.settings.steps.max(1);
+        let interval = self.settings.step_interval;
+        for i in 1..=steps {
+            let pos = lerp(start, pos, f64::from(i) / f64::from(steps));
+            self.push(Motion::MouseCursor(pos), interval);
+        }
+        self.cursor = pos;
+        self
+    }
+
+    /// Moves the mouse cursor to a position and clicks a mouse button.
+    pub fn click(&mut self, button: MouseButton, pos: [f64; 2]) -> &mut Self {
+        let hold_time = self.settings.hold_time;
+        self.move_to(pos);
+        self.button(ButtonState::Press, button.into(), hold_time);
+        self.release(button)
+    }
+
+    /// Drags with a mouse button held down from one position to another.
+    pub fn drag(&mut self, button: MouseButton, from: [f64; 2], to: [f64; 2]) -> &mut Self {
+        self.move_to(from);
+        self.press(button);
+        self.move_to(to);
+        self.release(button)
+    }
+
+    /// Scrolls with a mouse wheel, in lines.
+    pub fn scroll(&mut self, delta: [f64; 2]) -> &mut Self {
+        let interval = self.settings.event_interval;
+        self.push(Motion::MouseScroll(delta), interval)
+    }
+
+    /// Scrolls with a touchpad, in pixels spread over steps.
+    pub fn scroll_pixels(&mut self, delta: [f64; 2]) -> &mut Self {
+        let steps = self.settings.steps.max(1);
+        let interval = self.settings.step_interval;
+        let step = [delta[0] / f64::from(steps), delta[1] / f64::from(steps)];
+        for _ in 0..steps {
+            self.push(
+                Motion::Scroll(ScrollArgs::new(step, ScrollUnit::Pixels)),
+                interval,
+            );
+        }
+        self
+    }
+
+    /// Swipes with one or more fingers on a touch device.
+    ///
+    /// Every finger starts at its position and moves by the delta.
+    /// Positions are normalized 0..1.
+    /// The fingers get touch ids by their index.
+    pub fn swipe(&mut self, device: i64, fingers: &[[f64; 2]], delta: [f64; 2]) -> &mut Self {
+        let steps = self.settings.steps.max(1);
+        let interval = self.settings.step_interval;
+        let touch = |id: usize, pos: [f64; 2], touch: Touch| {
+            Motion::Touch(TouchArgs::new(device, id as i64, pos, 1.0, touch))
+        };
+        let end = |pos: [f64; 2]| [pos[0] + delta[0], pos[1] + delta[1]];
+        self.push_group(
+            fingers
+                .iter()
+                .enumerate()
+                .map(|(id, &pos)| touch(id, pos, Touch::Start)),
+            interval,
+        );
+        for i in 1..=steps {
+            let t = f64::from(i) / f64::from(steps);
+            self.push_group(
+                fingers
+                    .iter()
+                    .enumerate()
+                    .map(|(id, &pos)| touch(id, lerp(pos, end(pos), t), Touch::Move)),
+                interval,
+            );
+        }
+        self.push_group(
+            fingers
+                .iter()
+                .enumerate()
+                .map(|(id, &pos)| touch(id, end(pos), Touch::End)),
+            interval,
+        )
+    }
+
+    /// Moves a controller stick from one position to another.
+    ///
+    /// The horizontal and vertical axes of the stick are given as `axes`.
+    pub fn stick_sweep(
+        &mut self,
+        id: u32,
+        axes: [u8; 2],
+        from: [f64; 2],
+        to: [f64; 2],
+    ) -> &mut Self {
+        let steps = self.settings.steps.max(1);
+        let interval = self.settings.step_interval;
+        for i in 0..=steps {
+            let pos = lerp(from, to, f64::from(i) / f64::from(steps));
+            self.push_group(
+                axes.iter().zip(&pos).map(|(&axis, &position)| {
+                    Motion::ControllerAxis(ControllerAxisArgs::new(id, axis, position))
+                }),
+                interval,
+            );
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GenericEvent, PressEvent, ReleaseEvent, TextEvent};
+
+    fn time_stamps(events: &[Event]) -> Vec<TimeStamp> {
+        events.iter().filter_map(|e| e.time_stamp()).collect()
+    }
+
+    #[test]
+    fn test_type_text() {
+        let mut sim = Simulator::new(SimulatorSettings::new());
+        sim.type_text("aB\u{e9}");
+        let events = sim.take_events();
+        let presses: Vec<Button> = events.iter().filter_map(|e| e.press_args()).collect();
+        assert_eq!(
+            presses,
+            vec![
+                Button::Keyboard(Key::A),
+                Button::Keyboard(Key::LShift),
+                Button::Keyboard(Key::B),
+            ]
+        );
+        let releases: Vec<Button> = events.iter().filter_map(|e| e.release_args()).collect();
+        assert_eq!(
+            releases,
+            vec![
+                Button::Keyboard(Key::A),
+                Button::Keyboard(Key::B),
+                Button::Keyboard(Key::LShift),
+            ]
+        );
+        let text: String = events.iter().filter_map(|e| e.text_args()).collect();
+        assert_eq!(text, "aB\u{e9}");
+        let stamps = time_stamps(&events);
+        assert_eq!(stamps.len(), events.len());
+        assert!(stamps.windows(2).all(|w| w[0] < w[1]));
+        assert!(sim.events().is_empty());
+    }
+
+    #[test]
+    fn test_type_text_layout() {
+        use crate::keyboard_layout::GERMAN;
+
+        let mut sim = Simulator::new(SimulatorSettings {
+            layout: GERMAN,
+            ..SimulatorSettings::new()
+        });
+        sim.type_text("zê");
+        let presses: Vec<(Button, Option<i32>)> = sim
+            .events()
+            .iter()
+            .filter_map(|e| match *e {
+                Event::Input(Input::Button(args), _) if args.state == ButtonState::Press => {
+                    Some((args.button, args.scancode))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            presses,
+            vec![
+                (Key::Z.into(), Some(28)),
+                (Key::Caret.into(), Some(53)),
+                (Key::E.into(), Some(8)),
+            ]
+        );
+        let text: Vec<String> = sim.events().iter().filter_map(|e| e.text_args()).collect();
+        assert_eq!(text, vec!["z", "ê"]);
+    }
+
+    #[test]
+    fn test_chord() {
+        let mut sim = Simulator::new(SimulatorSettings::new());
+        sim.chord(ModifierKey::CTRL_SHIFT, Key::S);
+        let buttons: Vec<(ButtonState, Button)> = sim
+            .events()
+            .iter()
+            .filter_map(|e| match *e {
+                Event::Input(Input::Button(args), _) => Some((args.state, args.button)),
+                _ => None,
+            })
+            .collect();
+        use ButtonState::*;
+        assert_eq!(
+            buttons,
+            vec![
+                (Press, Key::LCtrl.into()),
+                (Press, Key::LShift.into()),
+                (Press, Key::S.into()),
+                (Release, Key::S.into()),
+                (Release, Key::LShift.into()),
+                (Release, Key::LCtrl.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drag() {
+        use crate::{mouse::*, MouseCursorEvent};
+
+        let mut sim = Simulator::new(SimulatorSettings::new());
+        sim.drag(MouseButton::Left, [10.0, 10.0], [50.0, 10.0]);
+        assert_eq!(sim.cursor(), [50.0, 10.0]);
+        let events = sim.take_events();
+        let last_pos = events.iter().rev().find_map(|e| e.mouse_cursor_args());
+        assert_eq!(last_pos, Some([50.0, 10.0]));
+
+        let mut gestures = MouseGestures::new(MouseGestureSettings::new());
+        let mut drag_end = None;
+        for e in &events {
+            gestures.event(e, |g| {
+                if let MouseGesture::DragEnd { position, .. } = g {
+                    drag_end = Some(position);
+                }
+            });
+        }
+        assert_eq!(drag_end, Some([50.0, 10.0]));
+    }
+
+    #[test]
+    fn test_swipe() {
+        use crate::touch::*;
+
+        let mut sim = Simulator::new(SimulatorSettings::new());
+        sim.swipe(0, &[[0.2, 0.5], [0.3, 0.5]], [0.4, 0.0]);
+        let events = sim.take_events();
+        let stamps = time_stamps(&events);
+        assert_eq!(stamps.len(), events.len());
+        assert!(stamps.windows(2).all(|w| w[0] < w[1]));
+
+        let mut recognizer = GestureRecognizer::new(GestureSettings::new());
+        let mut swipes = 0;
+        for e in &events {
+            recognizer.event(e, |e| {
+                if let Some(GestureArgs {
+                    gesture: Gesture::Swipe { .. },
+                    ..
+                }) = e.gesture_args()
+                {
+                    swipes += 1;
+                }
+            });
+        }
+        assert_eq!(swipes, 1);
+    }
+
+    #[test]
+    fn test_stick_sweep() {
+        use crate::ControllerAxisEvent;
+
+        let mut sim = Simulator::new(SimulatorSettings::new());
+        sim.stick_sweep(0, [0, 1], [0.0, 0.0], [1.0, -1.0]);
+        let axes: Vec<ControllerAxisArgs> = sim
+            .events()
+            .iter()
+            .filter_map(|e| e.controller_axis_args())
+            .collect();
+        assert_eq!(axes.len(), 18);
+        assert_eq!(axes[16], ControllerAxisArgs::new(0, 0, 1.0));
+        assert_eq!(axes[17], ControllerAxisArgs::new(0, 1, -1.0));
+        assert_eq!(sim.time(), 9 * 16);
+        let stamps = time_stamps(sim.events());
+        assert!(stamps.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{Event, Input, Motion};
+//! Back-end agnostic touch events and gesture recognition.
+
+use std::collections::HashMap;
+
+use crate::{Event, GenericEvent, Input, Motion, TimeStamp};
 
 /// Stores the touch state.
 #[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
@@ -137,6 +141,476 @@ impl TouchEvent for Event {
     }
 }
 
+/// The phase of a continuous gesture.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum GesturePhase {
+    /// The gesture started.
+    Start,
+    /// The gesture changed.
+    Update,
+    /// The gesture ended.
+    End,
+}
+
+/// Models gestures recognized from touches.
+///
+/// Positions are normalized 0..1, like touch positions.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub enum Gesture {
+    /// A short touch without moving.
+    Tap {
+        /// The position of the tap.
+        position: [f64; 2],
+        /// The number of taps in quick succession, e.g. `2` for a double tap.
+        count: u32,
+    },
+    /// A touch held down without moving.
+    LongPress {
+        /// The position of the touch.
+        position: [f64; 2],
+    },
+    /// A quick touch movement in one direction.
+    Swipe {
+        /// The position where the touch started.
+        start: [f64; 2],
+        /// The position where the touch ended.
+        end: [f64; 2],
+        /// The average velocity in normalized units per second.
+        velocity: [f64; 2],
+    },
+    /// Two touches moving towards or away from each other.
+    Pinch {
+        /// The phase of the gesture.
+        phase: GesturePhase,
+        /// The center between the touches.
+        center: [f64; 2],
+        /// The distance between the touches relative to the start of the gesture.
+        scale: f64,
+    },
+    /// Two touches rotating around each other.
+    Rotate {
+        /// The phase of the gesture.
+        phase: GesturePhase,
+        /// The center between the touches.
+        center: [f64; 2],
+        /// The clockwise rotation in radians since the start of the gesture.
+        ///
+        /// Rotations add up, so this can exceed a half turn.
+        angle: f64,
+    },
+}
+
+/// Gesture arguments.
+///
+/// Window backends might emit these directly, e.g. for touchpad pinch and rotate events,
+/// or they can be recognized from touches using [`GestureRecognizer`].
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct GestureArgs {
+    /// A unique identifier for touch device.
+    pub device: i64,
+    /// The recognized gesture.
+    pub gesture: Gesture,
+}
+
+/// When a gesture is recognized.
+pub trait GestureEvent: Sized {
+    /// Creates a gesture event.
+    ///
+    /// Preserves time stamp from original input event, if any.
+    fn from_gesture_args(args: &GestureArgs, old_event: &Self) -> Option<Self>;
+    /// Calls closure if this is a gesture event.
+    fn gesture<U, F>(&self, f: F) -> Option<U>
+    where
+        F: FnMut(&GestureArgs) -> U;
+    /// Returns gesture arguments.
+    fn gesture_args(&self) -> Option<GestureArgs> {
+        self.gesture(|args| *args)
+    }
+}
+
+impl GestureEvent for Event {
+    fn from_gesture_args(args: &GestureArgs, old_event: &Self) -> Option<Self> {
+        let timestamp = if let Event::Input(_, x) = old_event {
+            *x
+        } else {
+            None
+        };
+        Some(Event::Input(Input::Gesture(*args), timestamp))
+    }
+
+    fn gesture<U, F>(&self, mut f: F) -> Option<U>
+    where
+        F: FnMut(&GestureArgs) -> U,
+    {
+        match *self {
+            Event::Input(Input::Gesture(ref args), _) => Some(f(args)),
+            _ => None,
+        }
+    }
+}
+
+/// Thresholds used to recognize gestures.
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, PartialOrd, Debug)]
+pub struct GestureSettings {
+    /// The maximum distance a touch can move and still count as a tap or long press.
+    pub tap_distance: f64,
+    /// The maximum duration in milliseconds of a tap.
+    pub tap_time: TimeStamp,
+    /// The maximum time in milliseconds between taps to be counted together.
+    pub multi_tap_time: TimeStamp,
+    /// The duration in milliseconds before a touch is a long press.
+    pub long_press_time: TimeStamp,
+    /// The minimum distance of a swipe.
+    pub swipe_distance: f64,
+    /// The maximum duration in milliseconds of a swipe.
+    pub swipe_time: TimeStamp,
+    /// The minimum relative change in distance before a pinch starts.
+    pub pinch_threshold: f64,
+    /// The minimum rotation in radians before a rotation starts.
+    pub rotate_threshold: f64,
+}
+
+impl GestureSettings {
+    /// Creates new gesture settings with defaults.
+    ///
+    /// - `tap_distance`: 0.02
+    /// - `tap_time`: 300
+    /// - `multi_tap_time`: 300
+    /// - `long_press_time`: 500
+    /// - `swipe_distance`: 0.1
+    /// - `swipe_time`: 500
+    /// - `pinch_threshold`: 0.05
+    /// - `rotate_threshold`: 0.1
+    pub fn new() -> GestureSettings {
+        GestureSettings {
+            tap_distance: 0.02,
+            tap_time: 300,
+            multi_tap_time: 300,
+            long_press_time: 500,
+            swipe_distance: 0.1,
+            swipe_time: 500,
+            pinch_threshold: 0.05,
+            rotate_threshold: 0.1,
+        }
+    }
+}
+
+impl Default for GestureSettings {
+    fn default() -> GestureSettings {
+        GestureSettings::new()
+    }
+}
+
+/// Stores the state of a touch used to recognize gestures.
+#[derive(Copy, Clone, Debug)]
+struct Track {
+    start: [f64; 2],
+    position: [f64; 2],
+    start_time: f64,
+    /// Whether the touch moved beyond tap distance,
+    /// was part of a multi-touch or was a long press.
+    disqualified: bool,
+    long_press: bool,
+}
+
+/// Stores the state of a two-touch gesture.
+#[derive(Copy, Clone, Debug)]
+struct TwoTouch {
+    ids: [i64; 2],
+    distance: f64,
+    /// The angle between the touches at the last move.
+    angle: f64,
+    /// The sum of rotations since the start, which can exceed a half turn.
+    rotation: f64,
+    pinch: bool,
+    rotate: bool,
+}
+
+/// Stores the state of a device used to recognize gestures.
+#[derive(Clone, Debug, Default)]
+struct Device {
+    tracks: HashMap<i64, Track>,
+    two_touch: Option<TwoTouch>,
+    last_tap: Option<(f64, [f64; 2], u32)>,
+}
+
+fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    let dx = b[0] - a[0];
+    let dy = b[1] - a[1];
+    (dx * dx + dy * dy).sqrt()
+}
+
+fn center(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0]
+}
+
+fn angle(a: [f64; 2], b: [f64; 2]) -> f64 {
+    (b[1] - a[1]).atan2(b[0] - a[0])
+}
+
+/// Returns the change of angle between two moves, wrapped to [-π, π].
+fn angle_delta(angle: f64, last_angle: f64) -> f64 {
+    let delta = angle - last_angle;
+    if delta > std::f64::consts::PI {
+        delta - 2.0 * std::f64::consts::PI
+    } else if delta < -std::f64::consts::PI {
+        delta + 2.0 * std::f64::consts::PI
+    } else {
+        delta
+    }
+}
+
+/// Recognizes tap, double tap, long press, swipe, pinch and rotate gestures from touches.
+///
+/// Time is measured from the time stamps of input events.
+/// Between input events, time is advanced by update events,
+/// which is required to recognize long presses while a touch is not moving.
+#[derive(Clone, Debug, Default)]
+pub struct GestureRecognizer {
+    /// The thresholds used to recognize gestures.
+    pub settings: GestureSettings,
+    devices: HashMap<i64, Device>,
+    /// Current time in milliseconds.
+    time: f64,
+}
+
+impl GestureRecognizer {
+    /// Creates a new gesture recognizer.
+    pub fn new(settings: GestureSettings) -> GestureRecognizer {
+        GestureRecognizer {
+            settings,
+            devices: HashMap::new(),
+            time: 0.0,
+        }
+    }
+
+    /// Handles an event, calling the closure with the event and recognized gestures.
+    ///
+    /// Gestures are emitted after the touch event that completed them.
+    /// Long presses are emitted before the update event that completed them.
+    pub fn event<E, F>(&mut self, e: &E, mut f: F)
+    where
+        E: GenericEvent,
+        F: FnMut(&E),
+    {
+        if let Some(t) = e.time_stamp() {
+            self.time = self.time.max(t as f64);
+        }
+        if let Some(args) = e.update_args() {
+            self.time += args.dt * 1000.0;
+            let mut gestures = vec![];
+            for (&device, state) in &mut self.devices {
+                for track in state.tracks.values_mut() {
+                    if !track.disqualified
+                        && self.time - track.start_time >= self.settings.long_press_time as f64
+                    {
+                        track.disqualified = true;
+                        track.long_press = true;
+                        gestures.push(GestureArgs {
+                            device,
+                            gesture: Gesture::LongPress {
+                                position: track.position,
+                            },
+                        });
+                    }
+                }
+            }
+            gestures.sort_by_key(|g| g.device);
+            for args in &gestures {
+                if let Some(ev) = GestureEvent::from_gesture_args(args, e) {
+                    f(&ev);
+                }
+            }
+            f(e);
+            return;
+        }
+
+        f(e);
+        if let Some(args) = e.touch_args() {
+            let mut gestures = vec![];
+            self.touch(&args, |gesture| {
+                gestures.push(GestureArgs {
+                    device: args.device,
+                    gesture,
+                })
+            });
+            for args in &gestures {
+                if let Some(ev) = GestureEvent::from_gesture_args(args, e) {
+                    f(&ev);
+                }
+            }
+        }
+    }
+
+    fn touch<F>(&mut self, args: &TouchArgs, mut f: F)
+    where
+        F: FnMut(Gesture),
+    {
+        let settings = self.settings;
+        let time = self.time;
+        let device = self.devices.entry(args.device).or_default();
+        let position = args.position();
+        match args.touch {
+            Touch::Start => {
+                device.tracks.insert(
+                    args.id,
+                    Track {
+                        start: position,
+                        position,
+                        start_time: time,
+                        disqualified: !device.tracks.is_empty(),
+                        long_press: false,
+                    },
+                );
+                if device.tracks.len() == 2 {
+                    let mut ids = [0; 2];
+                    for (i, (&id, track)) in device.tracks.iter_mut().enumerate() {
+                        ids[i] = id;
+                        track.disqualified = true;
+                    }
+                    let a = device.tracks[&ids[0]].position;
+                    let b = device.tracks[&ids[1]].position;
+                    device.two_touch = Some(TwoTouch {
+                        ids,
+                        distance: distance(a, b),
+                        angle: angle(a, b),
+                        rotation: 0.0,
+                        pinch: false,
+                        rotate: false,
+                    });
+                } else {
+                    device.end_two_touch(&mut f);
+                }
+            }
+            Touch::Move => {
+                if let Some(track) = device.tracks.get_mut(&args.id) {
+                    track.position = position;
+                    if distance(track.start, position) > settings.tap_distance {
+                        track.disqualified = true;
+                    }
+                }
+                if let Some(two) = &mut device.two_touch {
+                    if two.ids.contains(&args.id) {
+                        let a = device.tracks[&two.ids[0]].position;
+                        let b = device.tracks[&two.ids[1]].position;
+                        let c = center(a, b);
+                        let scale = if two.distance > 0.0 {
+                            distance(a, b) / two.distance
+                        } else {
+                            1.0
+                        };
+                        let new_angle = angle(a, b);
+                        two.rotation += angle_delta(new_angle, two.angle);
+                        two.angle = new_angle;
+                        let rotation = two.rotation;
+                        if two.pinch || (scale - 1.0).abs() >= settings.pinch_threshold {
+                            let phase = if two.pinch {
+                                GesturePhase::Update
+                            } else {
+                                GesturePhase::Start
+                            };
+                            two.pinch = true;
+                            f(Gesture::Pinch {
+                                phase,
+                                center: c,
+                                scale,
+                            });
+                        }
+                        if two.rotate || rotation.abs() >= settings.rotate_threshold {
+                            let phase = if two.rotate {
+                                GesturePhase::Update
+                            } else {
+                                GesturePhase::Start
+                            };
+                            two.rotate = true;
+                            f(Gesture::Rotate {
+                                phase,
+                                center: c,
+                                angle: rotation,
+                            });
+                        }
+                    }
+                }
+            }
+            Touch::End | Touch::Cancel => {
+                if let Some(two) = device.two_touch {
+                    if two.ids.contains(&args.id) {
+                        device.end_two_touch(&mut f);
+                    }
+                }
+                let track = match device.tracks.remove(&args.id) {
+                    Some(x) => x,
+                    None => return,
+                };
+                if args.touch == Touch::Cancel || track.long_press {
+                    return;
+                }
+                let duration = time - track.start_time;
+                if !track.disqualified && duration <= settings.tap_time as f64 {
+                    let count = match device.last_tap {
+                        Some((last_time, last_pos, count))
+                            if time - last_time <= settings.multi_tap_time as f64
+                                && distance(last_pos, position) <= settings.tap_distance =>
+                        {
+                            count + 1
+                        }
+                        _ => 1,
+                    };
+                    device.last_tap = Some((time, position, count));
+                    f(Gesture::Tap { position, count });
+                } else if device.tracks.is_empty()
+                    && duration <= settings.swipe_time as f64
+                    && distance(track.start, position) >= settings.swipe_distance
+                {
+                    let secs = (duration / 1000.0).max(0.001);
+                    f(Gesture::Swipe {
+                        start: track.start,
+                        end: position,
+                        velocity: [
+                            (position[0] - track.start[0]) / secs,
+                            (position[1] - track.start[1]) / secs,
+                        ],
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Device {
+    fn end_two_touch<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(Gesture),
+    {
+        if let Some(two) = self.two_touch.take() {
+            let a = self.tracks.get(&two.ids[0]).map(|t| t.position);
+            let b = self.tracks.get(&two.ids[1]).map(|t| t.position);
+            if let (Some(a), Some(b)) = (a, b) {
+                let c = center(a, b);
+                if two.pinch {
+                    let scale = if two.distance > 0.0 {
+                        distance(a, b) / two.distance
+                    } else {
+                        1.0
+                    };
+                    f(Gesture::Pinch {
+                        phase: GesturePhase::End,
+                        center: c,
+                        scale,
+                    });
+                }
+                if two.rotate {
+                    f(Gesture::Rotate {
+                        phase: GesturePhase::End,
+                        center: c,
+                        angle: two.rotation,
+                    });
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,4 +661,167 @@ mod tests {
             .unwrap();
         assert_eq!(a, b);
     }
+
+    fn touch_event(id: i64, pos: [f64; 2], touch: Touch, time: TimeStamp) -> Event {
+        (TouchArgs::new(0, id, pos, 1.0, touch), Some(time)).into()
+    }
+
+    fn gestures(recognizer: &mut GestureRecognizer, events: &[Event]) -> Vec<Gesture> {
+        let mut out = vec![];
+        for e in events {
+            recognizer.event(e, |e: &Event| {
+                if let Some(args) = e.gesture_args() {
+                    out.push(args.gesture)
+                }
+            });
+        }
+        out
+    }
+
+    #[test]
+    fn test_input_gesture() {
+        let e: Event = (TouchArgs::new(0, 0, [0.0; 2], 1.0, Touch::Start), Some(0)).into();
+        let args = GestureArgs {
+            device: 0,
+            gesture: Gesture::Tap {
+                position: [0.5, 0.5],
+                count: 1,
+            },
+        };
+        let a: Option<Event> = GestureEvent::from_gesture_args(&args, &e);
+        let b: Option<Event> = a
+            .clone()
+            .unwrap()
+            .gesture(|args| GestureEvent::from_gesture_args(args, a.as_ref().unwrap()))
+            .unwrap();
+        assert_eq!(a, b);
+        assert_eq!(b.unwrap().time_stamp(), Some(0));
+    }
+
+    #[test]
+    fn test_tap_and_long_press() {
+        use crate::UpdateArgs;
+
+        let mut recognizer = GestureRecognizer::new(GestureSettings::new());
+        let p = [0.5, 0.5];
+        let events = [
+            touch_event(0, p, Touch::Start, 1000),
+            touch_event(0, p, Touch::End, 1100),
+            touch_event(1, p, Touch::Start, 1200),
+            touch_event(1, p, Touch::End, 1300),
+            touch_event(2, p, Touch::Start, 2000),
+            UpdateArgs { dt: 0.25 }.into(),
+            UpdateArgs { dt: 0.25 }.into(),
+            touch_event(2, p, Touch::End, 2600),
+        ];
+        assert_eq!(
+            gestures(&mut recognizer, &events),
+            vec![
+                Gesture::Tap {
+                    position: p,
+                    count: 1
+                },
+                Gesture::Tap {
+                    position: p,
+                    count: 2
+                },
+                Gesture::LongPress { position: p },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swipe_and_pinch() {
+        let mut recognizer = GestureRecognizer::new(GestureSettings::new());
+        let events = [
+            touch_event(0, [0.25, 0.5], Touch::Start, 0),
+            touch_event(0, [0.5, 0.5], Touch::Move, 100),
+            touch_event(0, [0.75, 0.5], Touch::End, 250),
+            touch_event(0, [0.25, 0.5], Touch::Start, 1000),
+            touch_event(1, [0.75, 0.5], Touch::Start, 1000),
+            touch_event(1, [1.25, 0.5], Touch::Move, 1100),
+            touch_event(1, [1.25, 0.5], Touch::End, 1200),
+            touch_event(0, [0.25, 0.5], Touch::End, 1200),
+        ];
+        assert_eq!(
+            gestures(&mut recognizer, &events),
+            vec![
+                Gesture::Swipe {
+                    start: [0.25, 0.5],
+                    end: [0.75, 0.5],
+                    velocity: [2.0, 0.0],
+                },
+                Gesture::Pinch {
+                    phase: GesturePhase::Start,
+                    center: [0.75, 0.5],
+                    scale: 2.0,
+                },
+                Gesture::Pinch {
+                    phase: GesturePhase::End,
+                    center: [0.75, 0.5],
+                    scale: 2.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rotate_across_pi() {
+        use std::f64::consts::PI;
+
+        let mut recognizer = GestureRecognizer::new(GestureSettings::new());
+        let a = [0.5, 0.5];
+        let at = |angle: f64| [a[0] + 0.25 * angle.cos(), a[1] + 0.25 * angle.sin()];
+        // The second finger rotates by 0.3 radians, crossing the angle π.
+        let (start, end) = (PI - 0.1, -PI + 0.2);
+        let events = [
+            touch_event(0, a, Touch::Start, 0),
+            touch_event(1, at(start), Touch::Start, 0),
+            touch_event(1, at(end), Touch::Move, 100),
+            touch_event(1, at(end), Touch::End, 200),
+        ];
+        let angles: Vec<(GesturePhase, f64)> = gestures(&mut recognizer, &events)
+            .into_iter()
+            .filter_map(|gesture| match gesture {
+                Gesture::Rotate { phase, angle, .. } => Some((phase, angle)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(angles.len(), 2);
+        assert_eq!(angles[0].0, GesturePhase::Start);
+        assert_eq!(angles[1].0, GesturePhase::End);
+        for &(_, angle) in &angles {
+            assert!((angle - 0.3).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_rotate_past_half_turn() {
+        use std::f64::consts::PI;
+
+        let mut recognizer = GestureRecognizer::new(GestureSettings::new());
+        let a = [0.5, 0.5];
+        let at = |angle: f64| [a[0] + 0.25 * angle.cos(), a[1] + 0.25 * angle.sin()];
+        // The second finger rotates a three quarter turn in steps of an eighth turn.
+        let mut events = vec![
+            touch_event(0, a, Touch::Start, 0),
+            touch_event(1, at(0.0), Touch::Start, 0),
+        ];
+        for i in 1..=6 {
+            events.push(touch_event(1, at(i as f64 * PI / 4.0), Touch::Move, i * 10));
+        }
+        events.push(touch_event(1, at(1.5 * PI), Touch::End, 100));
+        let angles: Vec<f64> = gestures(&mut recognizer, &events)
+            .into_iter()
+            .filter_map(|gesture| match gesture {
+                Gesture::Rotate { angle, .. } => Some(angle),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(angles.len(), 7);
+        for (i, &angle) in angles.iter().take(6).enumerate() {
+            assert!((angle - (i + 1) as f64 * PI / 4.0).abs() < 1e-9);
+        }
+        assert!((angles[6] - 1.5 * PI).abs() < 1e-9);
+    }
 }
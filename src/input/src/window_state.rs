@@ -0,0 +1,54 @@
+//! Window state events.
+
+use crate::Input;
+
+crate::event_trait! {
+    /// When the window is moved.
+    ///
+    /// The position is in screen coordinates of the top-left corner of the window.
+    pub trait MovedEvent {
+        args: [i32; 2],
+        from_args: from_moved,
+        accessor: moved,
+        args_getter: moved_args,
+        event: Input(Input::Moved),
+        test: test_input_moved([10, 20]),
+    }
+}
+
+crate::event_trait! {
+    /// When the window is minimized or restored from being minimized.
+    pub trait MinimizedEvent {
+        args: bool,
+        from_args: from_minimized,
+        accessor: minimized,
+        args_getter: minimized_args,
+        event: Input(Input::Minimized),
+        test: test_input_minimized(true),
+    }
+}
+
+crate::event_trait! {
+    /// When the window is maximized or restored from being maximized.
+    pub trait MaximizedEvent {
+        args: bool,
+        from_args: from_maximized,
+        accessor: maximized,
+        args_getter: maximized_args,
+        event: Input(Input::Maximized),
+        test: test_input_maximized(true),
+    }
+}
+
+crate::event_trait! {
+    /// When the window becomes hidden from view or visible again,
+    /// e.g. covered by other windows or moved to another virtual desktop.
+    pub trait OccludedEvent {
+        args: bool,
+        from_args: from_occluded,
+        accessor: occluded,
+        args_getter: occluded_args,
+        event: Input(Input::Occluded),
+        test: test_input_occluded(true),
+    }
+}
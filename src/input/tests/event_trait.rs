@@ -0,0 +1,93 @@
+extern crate input;
+
+use input::{
+    event_id::EventId, CustomArgs, CustomTypeError, Event, GenericEvent, IdleArgs, Input, Loop,
+    ResizeArgs,
+};
+
+/// Gyroscope motion of a gamepad.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GyroArgs {
+    /// The id of the gamepad.
+    pub id: u32,
+    /// The rotation in radians per second.
+    pub rotation: [f64; 3],
+}
+
+/// The event id of gyroscope events.
+pub const GYRO: EventId = EventId("gamepad/gyro");
+
+impl CustomArgs for GyroArgs {
+    fn event_id() -> EventId {
+        GYRO
+    }
+}
+
+input::event_trait! {
+    /// When the gyroscope of a gamepad moves.
+    pub trait GyroEvent {
+        args: &GyroArgs,
+        from_args: from_gyro_args,
+        accessor: gyro,
+        args_getter: gyro_args,
+        checked_args_getter: checked_gyro_args,
+        event: Custom,
+        test: test_input_gyro(GyroArgs { id: 1, rotation: [0.0, 0.5, 0.0] }),
+    }
+}
+
+input::event_trait! {
+    /// When the window is resized.
+    pub trait ResizedEvent {
+        args: &ResizeArgs,
+        from_args: from_resized_args,
+        accessor: resized,
+        args_getter: resized_args,
+        event: Input(Input::Resize),
+        test: test_input_resized(ResizeArgs {
+            window_size: [100.0, 50.0],
+            draw_size: [200, 100],
+        }),
+    }
+}
+
+input::event_trait! {
+    /// When the application is idle.
+    pub trait IdledEvent {
+        args: &IdleArgs,
+        from_args: from_idled_args,
+        accessor: idled,
+        args_getter: idled_args,
+        event: Loop(Loop::Idle),
+        test: test_input_idled(IdleArgs { dt: 0.25 }),
+    }
+}
+
+#[test]
+fn test_event_trait() {
+    let args = GyroArgs {
+        id: 2,
+        rotation: [1.0, 0.0, 0.0],
+    };
+    let old_event = Event::Input(Input::Focus(true), Some(5));
+    let e = Event::from_gyro_args(&args, &old_event).unwrap();
+    assert_eq!(e.event_id(), GYRO);
+    assert_eq!(e.time_stamp(), Some(5));
+    assert_eq!(e.gyro(|args| args.id), Some(2));
+    assert_eq!(old_event.gyro_args(), None);
+    assert_eq!(old_event.checked_gyro_args(), Ok(None));
+    // Custom events with the same id, but different arguments, are reported when checked.
+    let wrong = Event::Custom(GYRO, std::sync::Arc::new(()), None);
+    assert_eq!(wrong.gyro_args(), None);
+    assert_eq!(
+        wrong.checked_gyro_args(),
+        Err(CustomTypeError {
+            id: GYRO,
+            expected: std::any::type_name::<GyroArgs>(),
+        })
+    );
+
+    let e = Event::from_idled_args(&IdleArgs { dt: 0.5 }, &old_event).unwrap();
+    assert_eq!(e.time_stamp(), None);
+    assert_eq!(input::IdleEvent::idle_args(&e), Some(IdleArgs { dt: 0.5 }));
+}
@@ -15,21 +15,86 @@ fn test_encode_decode() {
         state: ButtonState::Press,
         button: Button::Keyboard(Key::A),
         scancode: None,
+        repeat: false,
+        modifiers: None,
     }));
     test(Input::Button(ButtonArgs {
         state: ButtonState::Release,
         button: Button::Keyboard(Key::A),
         scancode: None,
+        repeat: false,
+        modifiers: None,
+    }));
+    test(Input::Button(ButtonArgs {
+        state: ButtonState::Press,
+        button: Button::Keyboard(Key::A),
+        scancode: Some(4),
+        repeat: true,
+        modifiers: Some(keyboard::ModifierState {
+            sides: keyboard::ModifierSide::L_SHIFT,
+            locks: keyboard::LockKey::CAPS_LOCK,
+        }),
     }));
     test(Input::Move(Motion::MouseCursor([0.0, 0.0])));
+    test(Input::Move(Motion::MouseScroll([0.0, 1.0])));
+    test(Input::Move(Motion::Scroll(ScrollArgs {
+        delta: [0.0, 12.5],
+        unit: ScrollUnit::Pixels,
+        precise: true,
+        momentum: Some(GesturePhase::End),
+    })));
+    test(Input::Move(Motion::Pen(PenArgs {
+        tilt: [10.0, -20.0],
+        rotation: 90.0,
+        distance: Some(0.5),
+        eraser: true,
+        buttons: PenButtons::PRIMARY | PenButtons::SECONDARY,
+        ..PenArgs::new(0, [1.0, 2.0], 0.5)
+    })));
     test(Input::Text("hello".into()));
+    test(Input::Ime(Ime::Start));
+    test(Input::Ime(Ime::Preedit {
+        text: "hello".into(),
+        cursor: Some([1, 3]),
+    }));
+    test(Input::Ime(Ime::Commit("hello".into())));
+    test(Input::Ime(Ime::End));
     test(Input::Resize(ResizeArgs {
         window_size: [0.0, 0.0],
         draw_size: [0, 0],
     }));
+    test(Input::ScaleFactor(ScaleFactorArgs {
+        scale_factor: 1.5,
+        window_size: [100.0, 100.0],
+        draw_size: [150, 150],
+    }));
     test(Input::Focus(true));
     test(Input::Cursor(true));
+    test(Input::Moved([-10, 20]));
+    test(Input::Minimized(true));
+    test(Input::Maximized(false));
+    test(Input::Occluded(true));
     test(Input::Close(CloseArgs));
+    test(Input::Gesture(GestureArgs {
+        device: 0,
+        gesture: Gesture::Pinch {
+            phase: GesturePhase::Update,
+            center: [0.5, 0.5],
+            scale: 1.5,
+        },
+    }));
+    test(Input::Controller(ControllerConnectionArgs::new(
+        0,
+        ConnectionState::Added,
+        "X360 Controller".into(),
+        Some("030000005e0400008e02000014010000".into()),
+    )));
+    test(Input::Controller(ControllerConnectionArgs::new(
+        0,
+        ConnectionState::Removed,
+        "X360 Controller".into(),
+        None,
+    )));
 
     let test = |l| {
         let encoded = serde_json::to_string(&l).unwrap();
@@ -45,3 +110,16 @@ fn test_encode_decode() {
     test(Loop::Update(UpdateArgs { dt: 0.0 }));
     test(Loop::Idle(IdleArgs { dt: 0.0 }));
 }
+
+#[test]
+fn test_encode_decode_event() {
+    let test = |e: Event| {
+        let encoded = serde_json::to_string(&e).unwrap();
+        let decoded: Event = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded, e);
+        assert_eq!(decoded.time_stamp(), e.time_stamp());
+    };
+    test(Event::Input(Input::Text("hello".into()), Some(100)));
+    test(Event::Input(Input::Focus(false), None));
+    test(Event::Loop(Loop::Update(UpdateArgs { dt: 0.5 })));
+}
@@ -0,0 +1,759 @@
+#![deny(
+    rust_2018_idioms,
+    unused,
+    clippy::all,
+    clippy::doc_markdown,
+    missing_docs,
+    missing_copy_implementations,
+    missing_debug_implementations
+)]
+
+//! Derive macro for implementing `GenericEvent` on application event enums.
+//!
+//! Applications often need events of their own besides the events from Piston,
+//! e.g. network messages or events sent between threads.
+//! Deriving `GenericEvent` for an enum wrapping the Piston events
+//! lets the enum be used with generic libraries that handle events.
+//!
+//! ```ignore
+//! use input::{Input, Loop, TimeStamp};
+//! use input_derive::GenericEvent;
+//!
+//! #[derive(Clone, GenericEvent)]
+//! enum AppEvent {
+//!     #[event(input)]
+//!     Input(Input, Option<TimeStamp>),
+//!     #[event(loop)]
+//!     Loop(Loop),
+//!     #[event(id = "game/spawn")]
+//!     Spawn(u32),
+//! }
+//! ```
+//!
+//! Variants are marked with these attributes:
+//!
+//! - `#[event(input)]` wraps input events, either `(Input)` or `(Input, Option<TimeStamp>)`
+//! - `#[event(loop)]` wraps loop events, `(Loop)`
+//! - `#[event(custom)]` wraps custom events, `(EventId, Arc<dyn Any + Send + Sync>, Option<TimeStamp>)`
+//! - `#[event(id = "<api>/<event>")]` sets the event id of an application specific variant,
+//!   which must not be the event id of an event provided by Piston
+//!
+//! The input and loop variants are required.
+//! Without a custom variant, events created with `CustomEvent::from_custom_args` are `None`.
+//!
+//! The arguments of an application specific variant with a single field
+//! are the field, otherwise the whole event.
+//! Application specific variants have no time stamp.
+//!
+//! The generated code refers to the input crate as `::input`.
+//! When using it through another crate, set the path with e.g. `#[event(crate = "piston::input")]`.
+//!
+//! Accessors match the wrapped input and loop events by reference, without copying them.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Ident, LitStr, Path, Variant,
+};
+
+/// Derives `GenericEvent` and its super traits.
+///
+/// See the crate documentation for the supported attributes.
+#[proc_macro_derive(GenericEvent, attributes(event))]
+pub fn derive_generic_event(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// The variant storing the arguments of an event trait.
+#[derive(Copy, Clone)]
+enum Source {
+    /// The input variant.
+    Input,
+    /// The loop variant.
+    Loop,
+}
+
+/// The trait, constructor, constructor argument, accessor, accessor closure argument,
+/// the variant storing the arguments and the match arms of the accessor.
+type EventTrait = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    Source,
+    &'static str,
+);
+
+/// Event traits implemented by converting to `Event` in constructors,
+/// and by matching the borrowed `Input` or `Loop` in accessors.
+///
+/// The match arms of accessors are the same as the implementations for `Event`.
+/// `PressEvent` and `ReleaseEvent` are implemented for all `ButtonEvent`,
+/// and `CustomEvent` has generic methods, so these are not listed.
+const EVENT_TRAITS: &[EventTrait] = &[
+    (
+        "AfterRenderEvent",
+        "from_after_render_args",
+        "&_input::AfterRenderArgs",
+        "after_render",
+        "&_input::AfterRenderArgs",
+        Source::Loop,
+        "_input::Loop::AfterRender(ref args) => Some(f(args)),",
+    ),
+    (
+        "ButtonEvent",
+        "from_button_args",
+        "_input::ButtonArgs",
+        "button",
+        "_input::ButtonArgs",
+        Source::Input,
+        "_input::Input::Button(args) => Some(f(args)),",
+    ),
+    (
+        "CloseEvent",
+        "from_close_args",
+        "&_input::CloseArgs",
+        "close",
+        "&_input::CloseArgs",
+        Source::Input,
+        "_input::Input::Close(ref args) => Some(f(args)),",
+    ),
+    (
+        "ControllerAxisEvent",
+        "from_controller_axis_args",
+        "_input::ControllerAxisArgs",
+        "controller_axis",
+        "_input::ControllerAxisArgs",
+        Source::Input,
+        "_input::Input::Move(_input::Motion::ControllerAxis(args)) => Some(f(args)),",
+    ),
+    (
+        "ControllerConnectionEvent",
+        "from_controller_connection_args",
+        "&_input::ControllerConnectionArgs",
+        "controller_connection",
+        "&_input::ControllerConnectionArgs",
+        Source::Input,
+        "_input::Input::Controller(ref args) => Some(f(args)),",
+    ),
+    (
+        "CursorEvent",
+        "from_cursor",
+        "bool",
+        "cursor",
+        "bool",
+        Source::Input,
+        "_input::Input::Cursor(val) => Some(f(val)),",
+    ),
+    (
+        "FocusEvent",
+        "from_focused",
+        "bool",
+        "focus",
+        "bool",
+        Source::Input,
+        "_input::Input::Focus(val) => Some(f(val)),",
+    ),
+    (
+        "GestureEvent",
+        "from_gesture_args",
+        "&_input::GestureArgs",
+        "gesture",
+        "&_input::GestureArgs",
+        Source::Input,
+        "_input::Input::Gesture(ref args) => Some(f(args)),",
+    ),
+    (
+        "IdleEvent",
+        "from_idle_args",
+        "&_input::IdleArgs",
+        "idle",
+        "&_input::IdleArgs",
+        Source::Loop,
+        "_input::Loop::Idle(ref args) => Some(f(args)),",
+    ),
+    (
+        "ImeEvent",
+        "from_ime",
+        "&_input::Ime",
+        "ime",
+        "&_input::Ime",
+        Source::Input,
+        "_input::Input::Ime(ref ime) => Some(f(ime)),",
+    ),
+    (
+        "MaximizedEvent",
+        "from_maximized",
+        "bool",
+        "maximized",
+        "bool",
+        Source::Input,
+        "_input::Input::Maximized(val) => Some(f(val)),",
+    ),
+    (
+        "MinimizedEvent",
+        "from_minimized",
+        "bool",
+        "minimized",
+        "bool",
+        Source::Input,
+        "_input::Input::Minimized(val) => Some(f(val)),",
+    ),
+    (
+        "MouseCursorEvent",
+        "from_pos",
+        "[f64; 2]",
+        "mouse_cursor",
+        "[f64; 2]",
+        Source::Input,
+        "_input::Input::Move(_input::Motion::MouseCursor(pos)) => Some(f(pos)),",
+    ),
+    (
+        "MouseRelativeEvent",
+        "from_pos",
+        "[f64; 2]",
+        "mouse_relative",
+        "[f64; 2]",
+        Source::Input,
+        "_input::Input::Move(_input::Motion::MouseRelative(pos)) => Some(f(pos)),",
+    ),
+    (
+        "MouseScrollEvent",
+        "from_pos",
+        "[f64; 2]",
+        "mouse_scroll",
+        "[f64; 2]",
+        Source::Input,
+        "_input::Input::Move(_input::Motion::MouseScroll(pos)) => Some(f(pos)), \
+         _input::Input::Move(_input::Motion::Scroll(ref args)) => Some(f(args.ticks())),",
+    ),
+    (
+        "MovedEvent",
+        "from_moved",
+        "[i32; 2]",
+        "moved",
+        "[i32; 2]",
+        Source::Input,
+        "_input::Input::Moved(pos) => Some(f(pos)),",
+    ),
+    (
+        "OccludedEvent",
+        "from_occluded",
+        "bool",
+        "occluded",
+        "bool",
+        Source::Input,
+        "_input::Input::Occluded(val) => Some(f(val)),",
+    ),
+    (
+        "PenEvent",
+        "from_pen_args",
+        "&_input::PenArgs",
+        "pen",
+        "&_input::PenArgs",
+        Source::Input,
+        "_input::Input::Move(_input::Motion::Pen(ref args)) => Some(f(args)),",
+    ),
+    (
+        "RenderEvent",
+        "from_render_args",
+        "&_input::RenderArgs",
+        "render",
+        "&_input::RenderArgs",
+        Source::Loop,
+        "_input::Loop::Render(ref args) => Some(f(args)),",
+    ),
+    (
+        "ResizeEvent",
+        "from_resize_args",
+        "&_input::ResizeArgs",
+        "resize",
+        "&_input::ResizeArgs",
+        Source::Input,
+        "_input::Input::Resize(ref args) => Some(f(args)),",
+    ),
+    (
+        "ScaleFactorEvent",
+        "from_scale_factor_args",
+        "&_input::ScaleFactorArgs",
+        "scale_factor",
+        "&_input::ScaleFactorArgs",
+        Source::Input,
+        "_input::Input::ScaleFactor(ref args) => Some(f(args)),",
+    ),
+    (
+        "ScrollEvent",
+        "from_scroll_args",
+        "&_input::ScrollArgs",
+        "scroll",
+        "&_input::ScrollArgs",
+        Source::Input,
+        "_input::Input::Move(_input::Motion::MouseScroll(pos)) => \
+         Some(f(&_input::ScrollArgs::new(pos, _input::ScrollUnit::Lines))), \
+         _input::Input::Move(_input::Motion::Scroll(ref args)) => Some(f(args)),",
+    ),
+    (
+        "TextEvent",
+        "from_text",
+        "&str",
+        "text",
+        "&str",
+        Source::Input,
+        "_input::Input::Text(ref s) => Some(f(s)),",
+    ),
+    (
+        "TouchEvent",
+        "from_touch_args",
+        "&_input::TouchArgs",
+        "touch",
+        "&_input::TouchArgs",
+        Source::Input,
+        "_input::Input::Move(_input::Motion::Touch(ref args)) => Some(f(args)),",
+    ),
+    (
+        "UpdateEvent",
+        "from_update_args",
+        "&_input::UpdateArgs",
+        "update",
+        "&_input::UpdateArgs",
+        Source::Loop,
+        "_input::Loop::Update(ref args) => Some(f(args)),",
+    ),
+];
+
+/// The role of a variant.
+enum Kind {
+    /// Wraps `Input`, with or without time stamp.
+    Input,
+    /// Wraps `Loop`.
+    Loop,
+    /// Wraps the fields of `Event::Custom`.
+    Custom,
+    /// Application specific variant with an event id.
+    App(LitStr),
+}
+
+fn error<T>(span: Span, msg: &str) -> syn::Result<T> {
+    Err(syn::Error::new(span, msg))
+}
+
+fn variant_kind(variant: &Variant) -> syn::Result<Kind> {
+    let mut kind = None;
+    for attr in variant.attrs.iter().filter(|a| a.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            let new_kind =
+                if meta.path.is_ident("input") {
+                    match variant.fields {
+                        Fields::Unnamed(ref fields) if (1..=2).contains(&fields.unnamed.len()) => {}
+                        _ => {
+                            return error(
+                                variant.ident.span(),
+                                "expected `(Input)` or `(Input, Option<TimeStamp>)`",
+                            )
+                        }
+                    }
+                    Kind::Input
+                } else if meta.path.is_ident("loop") {
+                    match variant.fields {
+                        Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {}
+                        _ => return error(variant.ident.span(), "expected `(Loop)`"),
+                    }
+                    Kind::Loop
+                } else if meta.path.is_ident("custom") {
+                    match variant.fields {
+                        Fields::Unnamed(ref fields) if fields.unnamed.len() == 3 => {}
+                        _ => return error(
+                            variant.ident.span(),
+                            "expected `(EventId, Arc<dyn Any + Send + Sync>, Option<TimeStamp>)`",
+                        ),
+                    }
+                    Kind::Custom
+                } else if meta.path.is_ident("id") {
+                    let id: LitStr = meta.value()?.parse()?;
+                    let name = id.value();
+                    let valid = match name.find('/') {
+                        Some(i) => i > 0 && i + 1 < name.len(),
+                        None => false,
+                    };
+                    if !valid {
+                        return error(id.span(), "expected event id `<api>/<event>`");
+                    }
+                    Kind::App(id)
+                } else {
+                    return Err(meta.error("expected `input`, `loop`, `custom` or `id`"));
+                };
+            if kind.is_some() {
+                return Err(meta.error("duplicate event attribute"));
+            }
+            kind = Some(new_kind);
+            Ok(())
+        })?;
+    }
+    match kind {
+        Some(kind) => Ok(kind),
+        None => error(
+            variant.ident.span(),
+            "missing `#[event(...)]` attribute, e.g. `#[event(id = \"<api>/<event>\")]`",
+        ),
+    }
+}
+
+fn crate_path(input: &DeriveInput) -> syn::Result<Path> {
+    let mut path = syn::parse_quote!(::input);
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                let lit: LitStr = meta.value()?.parse()?;
+                path = lit.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `crate`"))
+            }
+        })?;
+    }
+    Ok(path)
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return error(
+            input.generics.params[0].span(),
+            "generic event types are not supported",
+        );
+    }
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => return error(name.span(), "GenericEvent can only be derived for enums"),
+    };
+    let krate = crate_path(input)?;
+
+    let mut input_variant = None;
+    let mut loop_variant = None;
+    let mut custom_variant = None;
+    let mut app_variants = vec![];
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let slot = match variant_kind(variant)? {
+            Kind::Input => &mut input_variant,
+            Kind::Loop => &mut loop_variant,
+            Kind::Custom => &mut custom_variant,
+            Kind::App(id) => {
+                app_variants.push((variant, id));
+                continue;
+            }
+        };
+        if slot.is_some() {
+            return error(ident.span(), "duplicate variant for this kind of event");
+        }
+        *slot = Some(variant);
+    }
+    let input_ident = match input_variant {
+        Some(variant) => &variant.ident,
+        None => return error(name.span(), "missing `#[event(input)]` variant"),
+    };
+    let loop_ident = match loop_variant {
+        Some(variant) => &variant.ident,
+        None => return error(name.span(), "missing `#[event(loop)]` variant"),
+    };
+    let input_time_stamp = input_variant
+        .map(|variant| variant.fields.len() == 2)
+        .unwrap_or(false);
+
+    let (input_old_event, input_from_event, input_new, input_time_stamp) = if input_time_stamp {
+        (
+            quote!(#name::#input_ident(_, time_stamp) =>
+                _input::Event::Input(_input::Input::Focus(true), time_stamp),),
+            quote!(_input::Event::Input(input, time_stamp) =>
+                Some(#name::#input_ident(input, time_stamp)),),
+            quote!(#name::#input_ident(input, None)),
+            quote!(#name::#input_ident(_, time_stamp) => time_stamp,),
+        )
+    } else {
+        (
+            quote!(),
+            quote!(_input::Event::Input(input, _) => Some(#name::#input_ident(input)),),
+            quote!(#name::#input_ident(input)),
+            quote!(),
+        )
+    };
+    let (custom_old_event, custom_from_event, custom_time_stamp, custom_event_id, custom_with_args) =
+        match custom_variant {
+            Some(variant) => {
+                let ident = &variant.ident;
+                (
+                    quote! {
+                        #name::#ident(id, ref args, time_stamp) =>
+                            _input::Event::Custom(id, args.clone(), time_stamp),
+                    },
+                    quote! {
+                        _input::Event::Custom(id, args, time_stamp) =>
+                            Some(#name::#ident(id, args, time_stamp)),
+                    },
+                    quote!(#name::#ident(_, _, time_stamp) => time_stamp,),
+                    quote!(#name::#ident(id, _, _) => id,),
+                    quote!(#name::#ident(_, ref args, _) => f(args),),
+                )
+            }
+            None => (
+                quote!(),
+                quote!(_input::Event::Custom(..) => None,),
+                quote!(),
+                quote!(),
+                quote!(),
+            ),
+        };
+    let custom_args = match custom_variant {
+        Some(variant) => {
+            let ident = &variant.ident;
+            quote! {
+                match *self {
+                    #name::#ident(id, ref args, _) if id == T::event_id() => {
+                        match args.clone().downcast::<T>() {
+                            Ok(args) => Ok(Some(args)),
+                            Err(_) => Err(_input::CustomTypeError {
+                                id,
+                                expected: ::std::any::type_name::<T>(),
+                            }),
+                        }
+                    }
+                    _ => Ok(None),
+                }
+            }
+        }
+        None => quote!(Ok(None)),
+    };
+
+    let app_ids: Vec<TokenStream> = app_variants
+        .iter()
+        .map(|(variant, id)| {
+            let ident = &variant.ident;
+            let pat = match variant.fields {
+                Fields::Unit => quote!(#name::#ident),
+                Fields::Unnamed(_) => quote!(#name::#ident(..)),
+                Fields::Named(_) => quote!(#name::#ident { .. }),
+            };
+            quote!(#pat => _input::event_id::EventId(#id),)
+        })
+        .collect();
+    // Event ids of Piston are only known to the input crate,
+    // so they are compared in a constant, which fails to compile on a match.
+    let app_checks = if app_variants.is_empty() {
+        quote!()
+    } else {
+        let asserts = app_variants.iter().map(|(_, id)| {
+            let msg = LitStr::new(
+                &format!(
+                    "event id `{}` is used by an event provided by Piston",
+                    id.value()
+                ),
+                id.span(),
+            );
+            quote_spanned!(id.span()=> const _: () = assert!(!is_builtin(#id), #msg);)
+        });
+        quote! {
+            /// Returns `true` if the name is the event id of an event provided by Piston.
+            const fn is_builtin(name: &str) -> bool {
+                let name = name.as_bytes();
+                let builtin = _input::event_id::BUILTIN;
+                let mut i = 0;
+                while i < builtin.len() {
+                    let other = builtin[i].0.as_bytes();
+                    if other.len() == name.len() {
+                        let mut j = 0;
+                        while j < name.len() && other[j] == name[j] {
+                            j += 1;
+                        }
+                        if j == name.len() {
+                            return true;
+                        }
+                    }
+                    i += 1;
+                }
+                false
+            }
+
+            #(#asserts)*
+        }
+    };
+    let app_with_args: Vec<TokenStream> = app_variants
+        .iter()
+        .map(|(variant, _)| {
+            let ident = &variant.ident;
+            match variant.fields {
+                Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+                    quote!(#name::#ident(ref args) => f(args as &dyn ::std::any::Any),)
+                }
+                Fields::Named(ref fields) if fields.named.len() == 1 => {
+                    let field = &fields.named[0].ident;
+                    quote!(#name::#ident { ref #field } => f(#field as &dyn ::std::any::Any),)
+                }
+                _ => {
+                    let pat = match variant.fields {
+                        Fields::Unit => quote!(#name::#ident),
+                        Fields::Unnamed(_) => quote!(#name::#ident(..)),
+                        Fields::Named(_) => quote!(#name::#ident { .. }),
+                    };
+                    quote!(#pat => f(self as &dyn ::std::any::Any),)
+                }
+            }
+        })
+        .collect();
+
+    let traits = EVENT_TRAITS
+        .iter()
+        .map(
+            |&(tr, ctor, ctor_arg, accessor, accessor_arg, source, arms)| {
+                let tr = Ident::new(tr, Span::call_site());
+                let ctor = Ident::new(ctor, Span::call_site());
+                let accessor = Ident::new(accessor, Span::call_site());
+                let ctor_arg: syn::Type = syn::parse_str(ctor_arg)?;
+                let accessor_arg: syn::Type = syn::parse_str(accessor_arg)?;
+                let arms: TokenStream = syn::parse_str(arms)?;
+                let pat = match source {
+                    Source::Input => quote!(#name::#input_ident(ref args, ..)),
+                    Source::Loop => quote!(#name::#loop_ident(ref args)),
+                };
+                Ok(quote! {
+                    impl _input::#tr for #name {
+                        fn #ctor(args: #ctor_arg, old_event: &Self) -> Option<Self> {
+                            <_input::Event as _input::#tr>::#ctor(args, &old_event_of(old_event))
+                                .and_then(from_event)
+                        }
+
+                        fn #accessor<U, F>(&self, mut f: F) -> Option<U>
+                        where
+                            F: FnMut(#accessor_arg) -> U,
+                        {
+                            match *self {
+                                #pat => match *args {
+                                    #arms
+                                    _ => None,
+                                },
+                                _ => None,
+                            }
+                        }
+                    }
+                })
+            },
+        )
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let with_args_f = if custom_variant.is_none() && app_variants.is_empty() {
+        quote!(f)
+    } else {
+        quote!(mut f)
+    };
+
+    Ok(quote! {
+        const _: () = {
+            use #krate as _input;
+
+            #app_checks
+
+            fn from_event(e: _input::Event) -> Option<#name> {
+                match e {
+                    #input_from_event
+                    _input::Event::Loop(args) => Some(#name::#loop_ident(args)),
+                    #custom_from_event
+                }
+            }
+
+            /// Returns an event with the time stamp of an event, for use as original event.
+            ///
+            /// Constructors only use the time stamp of the original event,
+            /// so the arguments are not copied.
+            fn old_event_of(e: &#name) -> _input::Event {
+                match *e {
+                    #input_old_event
+                    #custom_old_event
+                    _ => _input::Event::Loop(_input::Loop::AfterRender(_input::AfterRenderArgs)),
+                }
+            }
+
+            #(#traits)*
+
+            impl _input::CustomEvent for #name {
+                fn from_custom_args<T: _input::CustomArgs>(args: T, old_event: &Self) -> Option<Self> {
+                    <_input::Event as _input::CustomEvent>::from_custom_args(
+                        args,
+                        &old_event_of(old_event),
+                    )
+                    .and_then(from_event)
+                }
+
+                fn custom<T: _input::CustomArgs>(
+                    &self,
+                ) -> Result<Option<::std::sync::Arc<T>>, _input::CustomTypeError> {
+                    #custom_args
+                }
+            }
+
+            impl _input::GenericEvent for #name {
+                fn event_id(&self) -> _input::event_id::EventId {
+                    match *self {
+                        #name::#input_ident(ref input, ..) => input.event_id(),
+                        #name::#loop_ident(ref args) => args.event_id(),
+                        #custom_event_id
+                        #(#app_ids)*
+                    }
+                }
+
+                fn with_args<F, U>(&self, #with_args_f: F) -> U
+                where
+                    F: FnMut(&dyn ::std::any::Any) -> U,
+                {
+                    match *self {
+                        #name::#input_ident(ref input, ..) => input.with_args(f),
+                        #name::#loop_ident(ref args) => args.with_args(f),
+                        #custom_with_args
+                        #(#app_with_args)*
+                    }
+                }
+
+                fn time_stamp(&self) -> Option<_input::TimeStamp> {
+                    match *self {
+                        #input_time_stamp
+                        #custom_time_stamp
+                        _ => None,
+                    }
+                }
+            }
+
+            impl From<_input::Input> for #name {
+                fn from(input: _input::Input) -> Self {
+                    #input_new
+                }
+            }
+
+            impl From<_input::Loop> for #name {
+                fn from(args: _input::Loop) -> Self {
+                    #name::#loop_ident(args)
+                }
+            }
+
+            impl From<#name> for Option<_input::Input> {
+                fn from(e: #name) -> Self {
+                    match e {
+                        #name::#input_ident(input, ..) => Some(input),
+                        _ => None,
+                    }
+                }
+            }
+
+            impl From<#name> for Option<_input::Loop> {
+                fn from(e: #name) -> Self {
+                    match e {
+                        #name::#loop_ident(args) => Some(args),
+                        _ => None,
+                    }
+                }
+            }
+        };
+    })
+}
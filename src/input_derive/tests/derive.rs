@@ -0,0 +1,291 @@
+extern crate input;
+extern crate input_derive;
+
+use std::{any::Any, sync::Arc};
+
+use input::{
+    event_id::{self, EventId},
+    AfterRenderArgs, AfterRenderEvent, Button, ButtonArgs, ButtonEvent, ButtonState, CloseArgs,
+    CloseEvent, ConnectionState, ControllerAxisArgs, ControllerAxisEvent, ControllerConnectionArgs,
+    ControllerConnectionEvent, CursorEvent, CustomArgs, CustomEvent, Event, FileDrag, FocusEvent,
+    GenericEvent, Gesture, GestureArgs, GestureEvent, IdleArgs, IdleEvent, Ime, ImeEvent, Input,
+    Key, Loop, MaximizedEvent, MinimizedEvent, Motion, MouseButton, MouseCursorEvent,
+    MouseRelativeEvent, MouseScrollEvent, MovedEvent, OccludedEvent, PenArgs, PenEvent, PressEvent,
+    ReleaseEvent, RenderArgs, RenderEvent, ResizeArgs, ResizeEvent, ScaleFactorArgs,
+    ScaleFactorEvent, ScrollArgs, ScrollEvent, ScrollUnit, TextEvent, TimeStamp, Touch, TouchArgs,
+    TouchEvent, UpdateArgs, UpdateEvent,
+};
+use input_derive::GenericEvent;
+
+#[derive(Clone, Debug, GenericEvent)]
+enum AppEvent {
+    #[event(input)]
+    Input(Input, Option<TimeStamp>),
+    #[event(loop)]
+    Loop(Loop),
+    #[event(custom)]
+    Custom(EventId, Arc<dyn Any + Send + Sync>, Option<TimeStamp>),
+    #[event(id = "test/spawn")]
+    Spawn(u32),
+    #[event(id = "test/quit")]
+    Quit,
+}
+
+#[derive(Clone, Debug, GenericEvent)]
+enum MinimalEvent {
+    #[event(input)]
+    Input(Input),
+    #[event(loop)]
+    Loop(Loop),
+}
+
+#[derive(Debug, PartialEq)]
+struct Score(u32);
+
+impl CustomArgs for Score {
+    fn event_id() -> EventId {
+        EventId("test/score")
+    }
+}
+
+fn press() -> Input {
+    Input::Button(ButtonArgs::new(
+        ButtonState::Press,
+        Button::Keyboard(Key::A),
+        None,
+    ))
+}
+
+#[test]
+fn test_derive_input() {
+    let e = AppEvent::Input(press(), Some(10));
+    assert_eq!(e.event_id(), event_id::BUTTON);
+    assert_eq!(e.time_stamp(), Some(10));
+    assert_eq!(e.press_args(), Some(Button::Keyboard(Key::A)));
+    assert_eq!(e.update_args(), None);
+
+    let text: AppEvent = TextEvent::from_text("hi", &e).unwrap();
+    assert_eq!(text.text_args(), Some("hi".into()));
+    assert_eq!(text.time_stamp(), Some(10));
+
+    let input: Option<Input> = text.into();
+    assert_eq!(input, Some(Input::Text("hi".into())));
+    let e: AppEvent = press().into();
+    assert_eq!(e.time_stamp(), None);
+    assert!(e.button_args().is_some());
+}
+
+#[test]
+fn test_derive_loop() {
+    let e: AppEvent = Loop::Update(UpdateArgs { dt: 0.5 }).into();
+    assert_eq!(e.event_id(), event_id::UPDATE);
+    assert_eq!(e.update_args(), Some(UpdateArgs { dt: 0.5 }));
+    assert_eq!(
+        e.with_args(|args| args.downcast_ref::<UpdateArgs>().copied()),
+        Some(UpdateArgs { dt: 0.5 })
+    );
+    let l: Option<Loop> = e.into();
+    assert_eq!(l, Some(Loop::Update(UpdateArgs { dt: 0.5 })));
+}
+
+#[test]
+fn test_derive_custom() {
+    let old = AppEvent::Input(press(), Some(3));
+    let e = AppEvent::from_custom_args(Score(7), &old).unwrap();
+    assert_eq!(e.event_id(), EventId("test/score"));
+    assert_eq!(e.time_stamp(), Some(3));
+    assert_eq!(e.custom::<Score>().unwrap(), Some(Arc::new(Score(7))));
+
+    let minimal = MinimalEvent::Input(press());
+    assert!(MinimalEvent::from_custom_args(Score(7), &minimal).is_none());
+    assert_eq!(minimal.custom::<Score>().unwrap(), None);
+}
+
+#[test]
+fn test_derive_app() {
+    let spawn = AppEvent::Spawn(5);
+    assert_eq!(spawn.event_id(), EventId("test/spawn"));
+    assert_eq!(spawn.time_stamp(), None);
+    assert_eq!(
+        spawn.with_args(|args| args.downcast_ref::<u32>().copied()),
+        Some(5)
+    );
+    assert_eq!(spawn.button_args(), None);
+    let input: Option<Input> = spawn.clone().into();
+    assert_eq!(input, None);
+
+    let quit = AppEvent::Quit;
+    assert_eq!(quit.event_id(), EventId("test/quit"));
+    assert!(quit.with_args(|args| args.is::<AppEvent>()));
+
+    let update: AppEvent = UpdateEvent::from_dt(0.1, &spawn).unwrap();
+    assert_eq!(update.update_args(), Some(UpdateArgs { dt: 0.1 }));
+}
+
+#[test]
+fn test_derive_minimal() {
+    let e: MinimalEvent = press().into();
+    assert_eq!(e.event_id(), event_id::BUTTON);
+    let text = MinimalEvent::from_text("x", &e).unwrap();
+    assert_eq!(text.time_stamp(), None);
+    let e: Event = Event::Input(press(), None);
+    assert_eq!(e.button_args(), MinimalEvent::Input(press()).button_args());
+}
+
+#[test]
+fn test_derive_borrowed_accessors() {
+    let e = AppEvent::Input(Input::Text("hi".into()), None);
+    let ptr = match e {
+        AppEvent::Input(Input::Text(ref s), _) => s.as_ptr(),
+        _ => unreachable!(),
+    };
+    assert_eq!(e.text(|s| s.as_ptr()), Some(ptr));
+
+    let scroll = ScrollArgs::new([0.0, 2.0], ScrollUnit::Lines);
+    let e: AppEvent = Input::Move(Motion::Scroll(scroll)).into();
+    assert_eq!(e.scroll_args(), Some(scroll));
+    assert_eq!(e.mouse_scroll_args(), Some([0.0, 2.0]));
+    let e: AppEvent = Input::Move(Motion::MouseScroll([1.0, 0.0])).into();
+    assert_eq!(
+        e.scroll_args(),
+        Some(ScrollArgs::new([1.0, 0.0], ScrollUnit::Lines))
+    );
+    assert_eq!(e.event_id(), event_id::MOUSE_SCROLL);
+
+    let e = AppEvent::Custom(Score::event_id(), Arc::new(5_u32), None);
+    assert!(e.custom::<Score>().is_err());
+    assert!(e.with_args(|args| args.is::<Arc<dyn Any + Send + Sync>>()));
+}
+
+/// Returns one sample event for every variant of `Input`, `Motion` and `Loop`.
+fn samples() -> Vec<Event> {
+    let inputs = vec![
+        press(),
+        Input::Button(ButtonArgs::new(
+            ButtonState::Release,
+            Button::Mouse(MouseButton::Left),
+            None,
+        )),
+        Input::Move(Motion::MouseCursor([1.0, 2.0])),
+        Input::Move(Motion::MouseRelative([3.0, 4.0])),
+        Input::Move(Motion::MouseScroll([0.0, 1.0])),
+        Input::Move(Motion::Scroll(ScrollArgs::new(
+            [0.0, 40.0],
+            ScrollUnit::Pixels,
+        ))),
+        Input::Move(Motion::ControllerAxis(ControllerAxisArgs::new(0, 1, 0.5))),
+        Input::Move(Motion::Touch(TouchArgs::new(
+            0,
+            1,
+            [0.5, 0.5],
+            1.0,
+            Touch::Start,
+        ))),
+        Input::Move(Motion::Pen(PenArgs::new(0, [1.0, 2.0], 0.5))),
+        Input::Text("hi".into()),
+        Input::Ime(Ime::Commit("hi".into())),
+        Input::Resize(ResizeArgs {
+            window_size: [100.0, 100.0],
+            draw_size: [200, 200],
+        }),
+        Input::ScaleFactor(ScaleFactorArgs {
+            scale_factor: 2.0,
+            window_size: [100.0, 100.0],
+            draw_size: [200, 200],
+        }),
+        Input::Focus(true),
+        Input::Cursor(false),
+        Input::Moved([10, 20]),
+        Input::Minimized(true),
+        Input::Maximized(false),
+        Input::Occluded(true),
+        Input::Controller(ControllerConnectionArgs::new(
+            0,
+            ConnectionState::Added,
+            "Pad".into(),
+            None,
+        )),
+        Input::FileDrag(FileDrag::Cancel),
+        Input::Gesture(GestureArgs {
+            device: 0,
+            gesture: Gesture::Tap {
+                position: [0.5, 0.5],
+                count: 2,
+            },
+        }),
+        Input::Close(CloseArgs),
+    ];
+    let loops = vec![
+        Loop::Render(RenderArgs {
+            ext_dt: 0.0,
+            window_size: [100.0, 100.0],
+            draw_size: [200, 200],
+        }),
+        Loop::AfterRender(AfterRenderArgs),
+        Loop::Update(UpdateArgs { dt: 0.1 }),
+        Loop::Idle(IdleArgs { dt: 0.2 }),
+    ];
+    inputs
+        .into_iter()
+        .map(|input| Event::Input(input, Some(4)))
+        .chain(loops.into_iter().map(Event::Loop))
+        .collect()
+}
+
+#[test]
+fn test_derive_accessors_match_event() {
+    macro_rules! check {
+        ($e:expr, $app:expr, [$($accessor:ident),*]) => {
+            $(assert_eq!(
+                $app.$accessor(),
+                $e.$accessor(),
+                "`{}` of {:?}",
+                stringify!($accessor),
+                $e
+            );)*
+        };
+    }
+
+    for e in samples() {
+        let app = match e.clone() {
+            Event::Input(input, time_stamp) => AppEvent::Input(input, time_stamp),
+            Event::Loop(args) => AppEvent::Loop(args),
+            Event::Custom(..) => unreachable!(),
+        };
+        assert_eq!(app.event_id(), e.event_id());
+        assert_eq!(app.time_stamp(), e.time_stamp());
+        check!(
+            e,
+            app,
+            [
+                after_render_args,
+                button_args,
+                close_args,
+                controller_axis_args,
+                controller_connection_args,
+                cursor_args,
+                focus_args,
+                gesture_args,
+                idle_args,
+                ime_args,
+                maximized_args,
+                minimized_args,
+                mouse_cursor_args,
+                mouse_relative_args,
+                mouse_scroll_args,
+                moved_args,
+                occluded_args,
+                pen_args,
+                press_args,
+                release_args,
+                render_args,
+                resize_args,
+                scale_factor_args,
+                scroll_args,
+                text_args,
+                touch_args,
+                update_args
+            ]
+        );
+    }
+}
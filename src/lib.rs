@@ -89,13 +89,13 @@
 //! For example (a few libraries, there are many more):
 //!
 //! - [Image](https://github.com/pistondevelopers/image) library is standalone
-//! from both the core and the 2D graphics library,
-//! only connected through the 2D graphics backends.
+//!   from both the core and the 2D graphics library,
+//!   only connected through the 2D graphics backends.
 //! - [Piston's 2D graphics](https://github.com/pistondevelopers/graphics) is optional and can be used without a window backend.
-//! The window backend can be used without a 2D graphics backend, and so on.
+//!   The window backend can be used without a 2D graphics backend, and so on.
 //! - For image processing, see [Imageproc](https://github.com/pistondevelopers/imageproc).
 //! - [Dyon](https://github.com/pistondevelopers/dyon) is a Rusty dynamically typed scripting language,
-//! using a lifetime checker without garbage collection.
+//!   using a lifetime checker without garbage collection.
 //!
 //! For more information and an overview, see [Piston's README in the core repository](https://github.com/pistondevelopers/piston).
 //!
@@ -375,6 +375,41 @@ pub trait AdvancedWindow: Window + Sized {
         self.set_position(val);
         self
     }
+
+    /// Gets whether the window accepts text from an input method editor (IME).
+    ///
+    /// The default implementation returns `false`.
+    fn get_ime_enabled(&self) -> bool {
+        false
+    }
+
+    /// Sets whether the window accepts text from an input method editor (IME).
+    ///
+    /// When enabled, the window emits `Input::Ime` events while text is composed.
+    /// Committed text is also emitted as `Input::Text`.
+    ///
+    /// If the platform does not support this, it will have no effect.
+    /// The default implementation does nothing.
+    fn set_ime_enabled(&mut self, _value: bool) {}
+
+    /// Sets whether the window accepts text from an input method editor (IME).
+    ///
+    /// This method moves the current window data,
+    /// unlike [`set_ime_enabled()`](#method.set_ime_enabled), so
+    /// that it can be used in method chaining.
+    fn ime_enabled(mut self, value: bool) -> Self {
+        self.set_ime_enabled(value);
+        self
+    }
+
+    /// Sets the position of the IME candidate window, in window coordinates.
+    ///
+    /// This is usually the position of the text cursor,
+    /// such that the candidate window does not cover the composed text.
+    ///
+    /// If the platform does not support this, it will have no effect.
+    /// The default implementation does nothing.
+    fn set_ime_position(&mut self, _pos: [f64; 2]) {}
 }
 
 /// Trait for OpenGL specific operations on a window.
@@ -431,10 +466,10 @@ impl WindowSettings {
     ///
     /// - samples: 0
     /// - fullscreen: false
-    /// - exit_on_esc: false
-    /// - automatic_close: true
+    /// - `exit_on_esc`: false
+    /// - `automatic_close`: true
     /// - vsync: false
-    /// - graphics_api: None
+    /// - `graphics_api`: None
     /// - srgb: true
     /// - resizable: true
     /// - decorated: true
@@ -5,9 +5,9 @@
 //!
 //! Often used in servers as an event loop.
 
-use std::{error::Error, time::Duration};
+use std::{collections::VecDeque, error::Error, time::Duration};
 
-use input::Event;
+use input::{Event, Input};
 
 use crate::{AdvancedWindow, BuildFromWindowSettings, Position, Size, Window, WindowSettings};
 
@@ -17,6 +17,9 @@ use crate::{AdvancedWindow, BuildFromWindowSettings, Position, Size, Window, Win
 /// The size can be changed because the event loop does not emit
 /// [`Render`](../event_loop/trait.RenderEvent.html)
 /// events when the width or height is zero.
+///
+/// Events pushed with [`NoWindow::push_event`] are returned in order
+/// when polling for events, which is useful for testing.
 #[derive(Debug)]
 pub struct NoWindow {
     should_close: bool,
@@ -24,6 +27,9 @@ pub struct NoWindow {
     title: String,
     size: Size,
     pos: Position,
+    ime_enabled: bool,
+    ime_position: [f64; 2],
+    events: VecDeque<Event>,
 }
 
 impl NoWindow {
@@ -35,8 +41,32 @@ impl NoWindow {
             title: settings.get_title(),
             size: settings.get_size(),
             pos: Position { x: 0, y: 0 },
+            ime_enabled: false,
+            ime_position: [0.0, 0.0],
+            events: VecDeque::new(),
         }
     }
+
+    /// Adds an event to be returned when polling for events.
+    pub fn push_event<E: Into<Event>>(&mut self, e: E) {
+        self.events.push_back(e.into());
+    }
+
+    /// Adds events to be returned in order when polling for events.
+    ///
+    /// This can be used to script input, e.g. with events from `input::simulator::Simulator`.
+    pub fn push_events<I, E>(&mut self, events: I)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Event>,
+    {
+        self.events.extend(events.into_iter().map(|e| e.into()));
+    }
+
+    /// Gets the position of the IME candidate window.
+    pub fn get_ime_position(&self) -> [f64; 2] {
+        self.ime_position
+    }
 }
 
 impl Window for NoWindow {
@@ -55,15 +85,18 @@ impl Window for NoWindow {
     fn swap_buffers(&mut self) {}
 
     fn wait_event(&mut self) -> Event {
-        panic!("NoWindow will never return an input event");
+        match self.events.pop_front() {
+            Some(e) => e,
+            None => panic!("NoWindow will never return an input event"),
+        }
     }
 
     fn wait_event_timeout(&mut self, _timeout: Duration) -> Option<Event> {
-        None
+        self.events.pop_front()
     }
 
     fn poll_event(&mut self) -> Option<Event> {
-        None
+        self.events.pop_front()
     }
 
     fn draw_size(&self) -> Size {
@@ -113,11 +146,74 @@ impl AdvancedWindow for NoWindow {
         Some(self.pos)
     }
 
+    /// Pushes a moved event when the position changes.
     fn set_position<P: Into<Position>>(&mut self, val: P) {
-        self.pos = val.into();
+        let pos = val.into();
+        if pos != self.pos {
+            self.pos = pos;
+            self.push_event(Input::Moved([pos.x, pos.y]));
+        }
     }
 
     fn set_size<S: Into<Size>>(&mut self, val: S) {
         self.size = val.into();
     }
+
+    fn get_ime_enabled(&self) -> bool {
+        self.ime_enabled
+    }
+
+    fn set_ime_enabled(&mut self, value: bool) {
+        self.ime_enabled = value;
+    }
+
+    fn set_ime_position(&mut self, pos: [f64; 2]) {
+        self.ime_position = pos;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> NoWindow {
+        NoWindow::new(&WindowSettings::new("test", [100, 100]))
+    }
+
+    #[test]
+    fn test_push_event() {
+        let mut window = window();
+        window.push_event(Input::Focus(true));
+        window.push_event(Input::Text("a".into()));
+        window.push_event(Input::Text("b".into()));
+        assert_eq!(window.poll_event(), Some(Input::Focus(true).into()));
+        assert_eq!(window.poll_event(), Some(Input::Text("a".into()).into()));
+        assert_eq!(window.poll_event(), Some(Input::Text("b".into()).into()));
+        assert_eq!(window.poll_event(), None);
+    }
+
+    #[test]
+    fn test_ime() {
+        let mut window = window();
+        assert!(!window.get_ime_enabled());
+        window.set_ime_enabled(true);
+        assert!(window.get_ime_enabled());
+        window.set_ime_enabled(false);
+        assert!(!window.get_ime_enabled());
+
+        assert_eq!(window.get_ime_position(), [0.0, 0.0]);
+        window.set_ime_position([10.0, 20.0]);
+        assert_eq!(window.get_ime_position(), [10.0, 20.0]);
+    }
+
+    #[test]
+    fn test_set_position() {
+        let mut window = window();
+        assert_eq!(window.get_position(), Some(Position { x: 0, y: 0 }));
+        window.set_position([10, 20]);
+        assert_eq!(window.get_position(), Some(Position { x: 10, y: 20 }));
+        assert_eq!(window.poll_event(), Some(Input::Moved([10, 20]).into()));
+        window.set_position([10, 20]);
+        assert_eq!(window.poll_event(), None);
+    }
 }